@@ -0,0 +1,117 @@
+// Pack and unpack throughput for every kernel the CPU supports, at every bit
+// width, and StreamVByte decoding at every value byte length. Throughput is
+// reported in values per second.
+//
+// Inputs come from a fixed-seed xorshift generator masked to the width under
+// test, so every machine packs exactly the same bits.
+//
+//   cargo bench --bench bitpack
+//   cargo bench --bench bitpack -- 'unpack/avx2'
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simd_bitpacking_demo::block::BLOCK_LEN;
+use simd_bitpacking_demo::kernel::Kernel;
+use simd_bitpacking_demo::streamvbyte;
+
+// 128 Ki values: large enough to amortize call overhead, small enough to stay
+// in L2 so the numbers measure the kernels rather than memory bandwidth.
+const BLOCKS: usize = 1024;
+const SEED: u64 = 0x5eed_b175_9ac4_1234;
+
+fn blocks(bit_width: u8) -> Vec<[u32; BLOCK_LEN]> {
+    let mut x = SEED;
+    let mask = (1u64 << bit_width) - 1;
+    (0..BLOCKS)
+        .map(|_| {
+            std::array::from_fn(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x & mask) as u32
+            })
+        })
+        .collect()
+}
+
+// Words reserved per packed block; width 0 still gets one so that every block
+// has a slot to iterate over.
+fn words_per_block(bit_width: u8) -> usize {
+    (2 * bit_width as usize).max(1)
+}
+
+fn pack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pack");
+    group.throughput(Throughput::Elements((BLOCKS * BLOCK_LEN) as u64));
+    for kernel in Kernel::available() {
+        for width in 0..=32u8 {
+            let input = blocks(width);
+            let stride = words_per_block(width);
+            let mut out = vec![0u64; BLOCKS * stride];
+            group.bench_function(BenchmarkId::new(kernel.name(), width), |b| {
+                b.iter(|| {
+                    for (block, out) in input.iter().zip(out.chunks_exact_mut(stride)) {
+                        kernel.pack_block(block, width, out);
+                    }
+                    black_box(&mut out);
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn unpack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unpack");
+    group.throughput(Throughput::Elements((BLOCKS * BLOCK_LEN) as u64));
+    for kernel in Kernel::available() {
+        for width in 0..=32u8 {
+            let stride = words_per_block(width);
+            let mut packed = vec![0u64; BLOCKS * stride];
+            for (block, out) in blocks(width).iter().zip(packed.chunks_exact_mut(stride)) {
+                kernel.pack_block(block, width, out);
+            }
+            let mut out = vec![[0u32; BLOCK_LEN]; BLOCKS];
+            group.bench_function(BenchmarkId::new(kernel.name(), width), |b| {
+                b.iter(|| {
+                    for (packed, out) in black_box(&packed).chunks_exact(stride).zip(&mut out) {
+                        kernel.unpack_block(packed, width, out);
+                    }
+                    black_box(&mut out);
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn streamvbyte_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("streamvbyte");
+    group.throughput(Throughput::Elements((BLOCKS * BLOCK_LEN) as u64));
+    // Byte lengths 1 to 4, then random widths, which defeat branch prediction
+    // in the scalar loop.
+    let mut inputs: Vec<(String, Vec<u32>)> = [8, 16, 24, 32]
+        .map(|width| (format!("{}-byte", width / 8), blocks(width).concat()))
+        .into();
+    let mixed = (blocks(32).concat().iter())
+        .map(|&v| v >> (8 * (v % 4)))
+        .collect();
+    inputs.push(("mixed".to_string(), mixed));
+
+    for (name, values) in &inputs {
+        let mut encoded = Vec::new();
+        streamvbyte::encode(values, &mut encoded);
+        let (control, data) = encoded.split_at(values.len().div_ceil(4));
+        let mut out = vec![0u32; values.len()];
+        for kernel in Kernel::available() {
+            group.bench_function(BenchmarkId::new(kernel.name(), name), |b| {
+                b.iter(|| kernel.streamvbyte_decode(black_box(control), data, &mut out))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pack, unpack, streamvbyte_decode);
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+// Gorilla, Chimp, Chimp128 and ALP on the same synthetic float series.
+//
+// Before timing, prints the compressed size of every series in bits per value
+// and relative to Gorilla; throughput is then reported in values per second.
+// Series come from a fixed-seed xorshift generator.
+//
+//   cargo bench --bench floats
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simd_bitpacking_demo::{alp, chimp, gorilla};
+
+const LEN: usize = 10_000;
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+type Encode = fn(&[f64], &mut Vec<u8>);
+type Decode = fn(&[u8], usize, &mut Vec<f64>) -> simd_bitpacking_demo::Result<usize>;
+
+const CODECS: [(&str, Encode, Decode); 4] = [
+    ("gorilla", gorilla::encode, gorilla::decode),
+    ("chimp", chimp::encode, chimp::decode),
+    ("chimp128", chimp::encode128, chimp::decode128),
+    ("alp", alp::encode, alp::decode),
+];
+
+fn series() -> Vec<(&'static str, Vec<f64>)> {
+    let mut x = SEED;
+    let mut next = move || {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x
+    };
+
+    // Sensor reading with one decimal, drifting in steps of up to 0.1.
+    let mut walk = 20.0;
+    let temperature = (0..LEN)
+        .map(|_| {
+            walk += ((next() % 21) as f64 - 10.0) / 100.0;
+            (walk * 10.0f64).round() / 10.0
+        })
+        .collect();
+    // Price with two decimals.
+    let mut price = 100.0;
+    let prices = (0..LEN)
+        .map(|_| {
+            price += ((next() % 201) as f64 - 100.0) / 100.0;
+            (price * 100.0f64).round() / 100.0
+        })
+        .collect();
+    // Full-precision ratios, such as a CPU utilisation gauge.
+    let utilisation = (0..LEN)
+        .map(|_| (next() >> 11) as f64 / (1u64 << 53) as f64 * 100.0)
+        .collect();
+    // A few dozen set points visited in random order.
+    let set: Vec<f64> = (0..40).map(|_| (next() % 10_000) as f64 / 7.0).collect();
+    let set_points = (0..LEN).map(|_| set[(next() % 40) as usize]).collect();
+    // Integer counter stored as floats.
+    let counter = (0..LEN)
+        .map(|i| (3 * i as u64 + next() % 3) as f64)
+        .collect();
+
+    vec![
+        ("temperature", temperature),
+        ("price", prices),
+        ("utilisation", utilisation),
+        ("set-points", set_points),
+        ("counter", counter),
+    ]
+}
+
+fn ratios(series: &[(&str, Vec<f64>)]) {
+    println!(
+        "{:<12} {:<9} {:>10} {:>11}",
+        "series", "codec", "bits/value", "vs gorilla"
+    );
+    for (name, values) in series {
+        let mut gorilla_len = 0;
+        for (codec, encode, _) in CODECS {
+            let mut out = Vec::new();
+            encode(values, &mut out);
+            if codec == "gorilla" {
+                gorilla_len = out.len();
+            }
+            let bits = (out.len() * 8) as f64 / values.len() as f64;
+            let relative = out.len() as f64 / gorilla_len as f64;
+            println!("{name:<12} {codec:<9} {bits:>10.2} {relative:>10.2}x");
+        }
+    }
+}
+
+fn floats(c: &mut Criterion) {
+    let series = series();
+    ratios(&series);
+
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(LEN as u64));
+    for (name, values) in &series {
+        for (codec, encode, _) in CODECS {
+            group.bench_with_input(BenchmarkId::new(codec, name), values, |b, values| {
+                let mut out = Vec::new();
+                b.iter(|| {
+                    out.clear();
+                    encode(black_box(values), &mut out);
+                })
+            });
+        }
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(LEN as u64));
+    for (name, values) in &series {
+        for (codec, encode, decode) in CODECS {
+            let mut bytes = Vec::new();
+            encode(values, &mut bytes);
+            group.bench_with_input(BenchmarkId::new(codec, name), &bytes, |b, bytes| {
+                let mut out = Vec::with_capacity(LEN);
+                b.iter(|| {
+                    out.clear();
+                    decode(black_box(bytes), LEN, &mut out).unwrap();
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, floats);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+// Input: [count: u16 LE][framed blocks chosen per frame]
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simd_bitpacking_demo::adaptive;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((count, input)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let count = u16::from_le_bytes(*count) as usize;
+    let _ = adaptive::decode(input, count, &mut Vec::new());
+});
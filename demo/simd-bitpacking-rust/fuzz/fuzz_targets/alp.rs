@@ -0,0 +1,14 @@
+// Input: [count: u16 LE][encoded values]
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simd_bitpacking_demo::alp;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((count, input)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let count = u16::from_le_bytes(*count) as usize;
+    let _ = alp::decode(input, count, &mut Vec::new());
+});
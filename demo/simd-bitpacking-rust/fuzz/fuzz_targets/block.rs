@@ -0,0 +1,28 @@
+// Block decoders, range decoding and the scans that read block streams.
+//
+// Input: [count: u16 LE][start: u16 LE][encoded blocks]
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simd_bitpacking_demo::aggregate::{self, Aggregate};
+use simd_bitpacking_demo::block;
+use simd_bitpacking_demo::predicate::{self, Cmp};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((header, input)) = data.split_first_chunk::<4>() else {
+        return;
+    };
+    let count = u16::from_le_bytes([header[0], header[1]]) as usize;
+    let start = (u16::from_le_bytes([header[2], header[3]]) as usize).min(count);
+
+    let mut values = Vec::new();
+    if block::decode(input, count, &mut values).is_ok() {
+        assert_eq!(values.len(), count);
+    }
+    let _ = block::decode_range(input, count, start, count - start, &mut Vec::new());
+    for cmp in Cmp::ALL {
+        let _ = predicate::select_blocks(input, count, cmp, start as u32, &mut Vec::new());
+    }
+    let _ = aggregate::blocks(input, count, &mut Aggregate::default());
+});
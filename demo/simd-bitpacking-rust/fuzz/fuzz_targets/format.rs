@@ -0,0 +1,39 @@
+// Container parsing, then the codec named in the header on its payload.
+//
+// Input: one or more framed blocks.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simd_bitpacking_demo::format::{Block, Codec};
+use simd_bitpacking_demo::{
+    alp, block, chimp, delta, dictionary, dod, frame_of_ref, gorilla, leb128, parquet_rle, pfor,
+    simple8b, streamvbyte,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = data;
+    while let Ok((framed, len)) = Block::read(input) {
+        // Cap the count so that width-0 blocks cannot expand without bound.
+        let count = (framed.count as usize).min(1 << 16);
+        let payload = framed.payload;
+        let _ = match framed.codec {
+            Codec::Bitpack => block::decode(payload, count, &mut Vec::new()),
+            Codec::For => frame_of_ref::decode(payload, count, &mut Vec::new()),
+            Codec::For64 => frame_of_ref::decode64(payload, count, &mut Vec::new()),
+            Codec::Pfor => pfor::decode(payload, count, &mut Vec::new()),
+            Codec::Dod => dod::decode(payload, count, &mut Vec::new()),
+            Codec::Gorilla => gorilla::decode(payload, count, &mut Vec::new()),
+            Codec::Chimp => chimp::decode(payload, count, &mut Vec::new()),
+            Codec::Chimp128 => chimp::decode128(payload, count, &mut Vec::new()),
+            Codec::Alp => alp::decode(payload, count, &mut Vec::new()),
+            Codec::Simple8b => simple8b::decode(payload, count, &mut Vec::new()),
+            Codec::Leb128 => leb128::decode(payload, count, &mut Vec::new()),
+            Codec::StreamVByte => streamvbyte::decode(payload, count, &mut Vec::new()),
+            Codec::ParquetRle => parquet_rle::decode(payload, count, &mut Vec::new()),
+            Codec::Dictionary => dictionary::decode(payload, count, &mut Vec::new()),
+            Codec::Delta => delta::decode_series(payload, count, &mut Vec::new()),
+        };
+        input = &input[len..];
+    }
+});
@@ -0,0 +1,21 @@
+// 32- and 64-bit FOR decoders and the aggregates that read them.
+//
+// Input: [count: u16 LE][encoded blocks]
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simd_bitpacking_demo::aggregate::{self, Aggregate};
+use simd_bitpacking_demo::frame_of_ref;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((count, input)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let count = u16::from_le_bytes(*count) as usize;
+
+    let _ = frame_of_ref::decode(input, count, &mut Vec::new());
+    let _ = frame_of_ref::decode64(input, count, &mut Vec::new());
+    let _ = aggregate::frame_of_ref(input, count, &mut Aggregate::default());
+    let _ = aggregate::frame_of_ref64(input, count, &mut Aggregate::default());
+});
@@ -0,0 +1,12 @@
+// Input: a complete stream, end marker included.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simd_bitpacking_demo::stream::Decoder;
+
+fuzz_target!(|data: &[u8]| {
+    let mut decoder = Decoder::new(data);
+    decoder.by_ref().count();
+    let _ = decoder.error();
+});
@@ -0,0 +1,362 @@
+// Automatic codec selection, one framed block at a time.
+//
+// Columns change character along the way: a counter is best delta-encoded, a
+// status code column wants a dictionary, a sparse gauge RLE. Instead of
+// fixing one codec per column, the selector splits the values into frames of
+// up to 4096, trial-encodes a sample of each frame with every candidate codec
+// and writes the frame with the cheapest one as a `format::Block`, so the
+// codec id travels in the block header and any reader of the format can
+// decode it.
+//
+// The sample is four evenly spaced runs of 128 consecutive values, encoded
+// one by one so that the steps RLE and delta feed on survive and no codec
+// sees the jumps between runs. "Cheapest" is decided by a
+// `CostModel`: the smallest sample encoding by default, or any trade-off
+// between size and decode speed a caller plugs in.
+//
+// Selection is per frame, not per 128-value block, on purpose. Each framed
+// block costs 20 bytes of header and checksum, a third of a 4-bit block of
+// 128 values but under 1% of a frame, and a per-block choice would
+// trial-encode every value with every candidate instead of a sample. Codecs
+// that adapt inside a frame still do so per 128-value block: bitpacking, FOR
+// and PFOR pick a width for each one.
+
+use crate::block::{max_bits64, BLOCK_LEN};
+use crate::error::{Error, Result};
+use crate::format::{Block, Codec};
+use crate::{
+    block, delta, dictionary, frame_of_ref, leb128, parquet_rle, pfor, simple8b, streamvbyte,
+};
+
+/// Values per framed block.
+pub const FRAME_LEN: usize = 1 << 12;
+
+// Sampled runs per frame, each of BLOCK_LEN consecutive values.
+const SAMPLE_RUNS: usize = 4;
+
+/// Codecs tried by [`Selector::new`]: plain bitpacking, frame of reference,
+/// delta, patched FOR, RLE and dictionary.
+pub const DEFAULT_CANDIDATES: [Codec; 6] = [
+    Codec::Bitpack,
+    Codec::For64,
+    Codec::Delta,
+    Codec::Pfor,
+    Codec::ParquetRle,
+    Codec::Dictionary,
+];
+
+/// Scores a trial encoding; the selector keeps the codec with the lowest
+/// score.
+///
+/// Closures `Fn(Codec, usize, usize) -> f64` are cost models too, for
+/// example to charge the slower decoders for the values they decode:
+///
+/// ```
+/// use simd_bitpacking_demo::adaptive::Selector;
+/// use simd_bitpacking_demo::format::Codec;
+///
+/// let selector = Selector::new().cost_model(|codec, bytes, values| {
+///     let ns_per_value = match codec {
+///         Codec::Pfor | Codec::Dictionary => 2.0,
+///         _ => 1.0,
+///     };
+///     bytes as f64 + 0.5 * ns_per_value * values as f64
+/// });
+/// ```
+pub trait CostModel {
+    /// Cost of `codec` encoding `values` sample values into `bytes` bytes.
+    fn cost(&self, codec: Codec, bytes: usize, values: usize) -> f64;
+}
+
+/// Picks the smallest encoding.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Smallest;
+
+impl CostModel for Smallest {
+    fn cost(&self, _: Codec, bytes: usize, _: usize) -> f64 {
+        bytes as f64
+    }
+}
+
+impl<F: Fn(Codec, usize, usize) -> f64> CostModel for F {
+    fn cost(&self, codec: Codec, bytes: usize, values: usize) -> f64 {
+        self(codec, bytes, values)
+    }
+}
+
+/// Chooses a codec per frame of u64 values.
+#[derive(Debug, Clone)]
+pub struct Selector<M = Smallest> {
+    candidates: Vec<Codec>,
+    model: M,
+}
+
+impl Default for Selector {
+    fn default() -> Self {
+        Selector {
+            candidates: DEFAULT_CANDIDATES.to_vec(),
+            model: Smallest,
+        }
+    }
+}
+
+impl Selector {
+    /// Selects among [`DEFAULT_CANDIDATES`] by size.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M: CostModel> Selector<M> {
+    /// Scores candidates with `model` instead.
+    pub fn cost_model<N: CostModel>(self, model: N) -> Selector<N> {
+        Selector {
+            candidates: self.candidates,
+            model,
+        }
+    }
+
+    /// Tries only `codecs`.
+    ///
+    /// Any integer codec can be a candidate: those above, plus FOR, Simple8b,
+    /// LEB128 and StreamVByte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `codecs` is empty or holds a codec for timestamps or floats.
+    pub fn candidates(mut self, codecs: &[Codec]) -> Self {
+        assert!(!codecs.is_empty(), "no candidate codecs");
+        for &codec in codecs {
+            assert!(
+                is_integer(codec),
+                "{} is not an integer codec",
+                codec.name()
+            );
+        }
+        self.candidates = codecs.to_vec();
+        self
+    }
+
+    /// The codec with the lowest cost on a sample of `values`.
+    ///
+    /// Candidates that cannot represent the sample, such as 32-bit codecs for
+    /// larger values, are skipped; if none can, 64-bit FOR is chosen.
+    pub fn choose(&self, values: &[u64]) -> Codec {
+        let runs = sample(values);
+        let sampled = runs.iter().map(|run| run.len()).sum();
+        let mut best = (Codec::For64, f64::INFINITY);
+        let mut trial = Vec::new();
+        'candidates: for &codec in &self.candidates {
+            trial.clear();
+            for run in &runs {
+                if !encode_with(codec, run, &mut trial) {
+                    continue 'candidates;
+                }
+            }
+            let cost = self.model.cost(codec, trial.len(), sampled);
+            if cost < best.1 {
+                best = (codec, cost);
+            }
+        }
+        best.0
+    }
+
+    /// Appends `values` to `out` as framed blocks of up to [`FRAME_LEN`]
+    /// values, each encoded with the codec chosen for it.
+    pub fn encode(&self, values: &[u64], out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        for frame in values.chunks(FRAME_LEN) {
+            let mut codec = self.choose(frame);
+            payload.clear();
+            // The sample may fit a 32-bit codec that the whole frame does not.
+            if !encode_with(codec, frame, &mut payload) {
+                codec = Codec::For64;
+                encode_with(codec, frame, &mut payload);
+            }
+            let block = Block {
+                codec,
+                bit_width: header_width(codec, frame),
+                count: frame.len() as u32,
+                payload: &payload,
+            };
+            block.write(out);
+        }
+    }
+}
+
+/// Decodes `count` values written by [`Selector::encode`] and appends them to
+/// `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let (block, len) = Block::read(&input[pos..])?;
+        let count = block.count as usize;
+        if count > remaining {
+            return Err(Error::Corrupt("block holds more values than remain"));
+        }
+        if count > FRAME_LEN {
+            return Err(Error::Corrupt("block exceeds frame length"));
+        }
+        let payload = block.payload;
+        let read = match block.codec {
+            Codec::Bitpack => decode32(payload, count, block::decode, out),
+            Codec::For => decode32(payload, count, frame_of_ref::decode, out),
+            Codec::Pfor => decode32(payload, count, pfor::decode, out),
+            Codec::StreamVByte => decode32(payload, count, streamvbyte::decode, out),
+            Codec::ParquetRle => decode32(payload, count, parquet_rle::decode, out),
+            Codec::For64 => frame_of_ref::decode64(payload, count, out),
+            Codec::Delta => delta::decode_series(payload, count, out),
+            Codec::Simple8b => simple8b::decode(payload, count, out),
+            Codec::Leb128 => leb128::decode(payload, count, out),
+            Codec::Dictionary => dictionary::decode(payload, count, out),
+            _ => return Err(Error::Corrupt("block codec is not an integer codec")),
+        }?;
+        if read != payload.len() {
+            return Err(Error::Corrupt("trailing bytes in block payload"));
+        }
+        pos += len;
+        remaining -= count;
+    }
+    Ok(pos)
+}
+
+fn decode32(
+    payload: &[u8],
+    count: usize,
+    decode: fn(&[u8], usize, &mut Vec<u32>) -> Result<usize>,
+    out: &mut Vec<u64>,
+) -> Result<usize> {
+    // `count` comes from the header; reserve no more than a frame up front.
+    let mut values = Vec::with_capacity(count.min(FRAME_LEN));
+    let read = decode(payload, count, &mut values)?;
+    out.extend(values.into_iter().map(u64::from));
+    Ok(read)
+}
+
+fn is_integer(codec: Codec) -> bool {
+    matches!(
+        codec,
+        Codec::Bitpack
+            | Codec::For
+            | Codec::For64
+            | Codec::Pfor
+            | Codec::Simple8b
+            | Codec::Leb128
+            | Codec::StreamVByte
+            | Codec::ParquetRle
+            | Codec::Dictionary
+            | Codec::Delta
+    )
+}
+
+// The whole frame if it is short, else evenly spaced runs of BLOCK_LEN values.
+fn sample(values: &[u64]) -> Vec<&[u64]> {
+    if values.len() <= SAMPLE_RUNS * BLOCK_LEN {
+        return vec![values];
+    }
+    let stride = (values.len() - BLOCK_LEN) / (SAMPLE_RUNS - 1);
+    (0..SAMPLE_RUNS)
+        .map(|run| &values[run * stride..run * stride + BLOCK_LEN])
+        .collect()
+}
+
+// Appends `values` encoded with `codec`, or returns false without writing if
+// the codec cannot represent them.
+fn encode_with(codec: Codec, values: &[u64], out: &mut Vec<u8>) -> bool {
+    let narrow = || -> Option<Vec<u32>> { values.iter().map(|&v| v.try_into().ok()).collect() };
+    match codec {
+        Codec::For64 => frame_of_ref::encode64(values, out),
+        Codec::Delta => delta::encode_series(values, out),
+        Codec::Leb128 => leb128::encode(values, out),
+        Codec::Dictionary => dictionary::encode(values, out),
+        Codec::Simple8b => return simple8b::encode(values, out).is_ok(),
+        _ => {
+            let Some(values) = narrow() else {
+                return false;
+            };
+            match codec {
+                Codec::Bitpack => block::encode(&values, out),
+                Codec::For => frame_of_ref::encode(&values, out),
+                Codec::Pfor => _ = pfor::encode(&values, out),
+                Codec::StreamVByte => streamvbyte::encode(&values, out),
+                Codec::ParquetRle => parquet_rle::encode(&values, out),
+                _ => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Encodes `values` with `codec` into `payload`, replacing what it held, and
+/// returns the block that frames it, with the bit width the codec packs at.
+///
+/// Returns `None` if the codec cannot represent the values.
+///
+/// # Panics
+///
+/// Panics if `values` holds more than `u32::MAX` values.
+pub fn encode_block<'a>(
+    codec: Codec,
+    values: &[u64],
+    payload: &'a mut Vec<u8>,
+) -> Option<Block<'a>> {
+    payload.clear();
+    if !encode_with(codec, values, payload) {
+        return None;
+    }
+    Some(Block {
+        codec,
+        bit_width: header_width(codec, values),
+        count: u32::try_from(values.len()).expect("block exceeds u32::MAX values"),
+        payload,
+    })
+}
+
+// The bit width to record in the header of a `codec` block of `values`: the
+// widest width its payload packs at, or 0 for codecs without one. Values a
+// 32-bit codec cannot hold give 0, like a block that codec could not have
+// written.
+pub(crate) fn header_width(codec: Codec, values: &[u64]) -> u8 {
+    match codec {
+        Codec::Bitpack | Codec::ParquetRle => max_bits64(values),
+        Codec::For | Codec::For64 => widest_residual(values),
+        Codec::Pfor => {
+            let values: Option<Vec<u32>> = values.iter().map(|&v| u32::try_from(v).ok()).collect();
+            let Some(values) = values else {
+                return 0;
+            };
+            let widths = values.chunks(BLOCK_LEN).map(pfor::coverage_width);
+            widths.max().unwrap_or(0)
+        }
+        Codec::Delta => {
+            let mut residuals = vec![0; values.len()];
+            delta::encode_u64(values.first().copied().unwrap_or(0), values, &mut residuals);
+            widest_residual(&residuals)
+        }
+        Codec::Dictionary => {
+            let mut ids = dictionary::Dictionary::new();
+            for v in values {
+                ids.insert(v);
+            }
+            // Past the limit the column is written plain, without ids.
+            if ids.len() <= dictionary::DEFAULT_MAX_LEN {
+                ids.bit_width()
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    }
+}
+
+// Widest FOR residual over the 128-value blocks of `values`.
+fn widest_residual(values: &[u64]) -> u8 {
+    let blocks = values.chunks(BLOCK_LEN).map(|block| {
+        let min = block.iter().copied().min().unwrap_or(0);
+        max_bits64(&[block.iter().fold(0, |or, &v| or | (v - min))])
+    });
+    blocks.max().unwrap_or(0)
+}
@@ -0,0 +1,176 @@
+// Aggregates computed in the compressed domain.
+//
+// Sum, minimum and maximum are read off the packed residuals without
+// materializing the values: a FOR block with minimum `m` and residuals `r`
+// holds the values `m + r`, so its sum is `len * m + sum(r)`, its minimum is
+// `m + min(r)` and its maximum is `m + max(r)`. The kernels reduce the
+// residuals in registers, and a block of width 0 is answered from its header
+// alone. Plain bitpacked blocks are FOR blocks with a minimum of 0.
+//
+// Every result equals the one obtained by decoding the values and folding
+// them one by one, including for corrupt input whose values wrap around.
+
+use crate::bitpack::{packed_bytes, read_bytes, unpack, unpack64};
+use crate::block::BLOCK_LEN;
+use crate::error::{Error, Result};
+use crate::frame_of_ref::HEADER_LEN64;
+use crate::kernel::Kernel;
+
+/// Count, sum, minimum and maximum of a set of values.
+///
+/// `min` and `max` are `None` for an empty set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Aggregate {
+    pub count: u64,
+    pub sum: u128,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl Aggregate {
+    /// Adds one value.
+    pub fn push(&mut self, value: u64) {
+        self.merge(Aggregate {
+            count: 1,
+            sum: value as u128,
+            min: Some(value),
+            max: Some(value),
+        });
+    }
+
+    /// Adds every value aggregated by `other`.
+    pub fn merge(&mut self, other: Aggregate) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.into_iter().chain(other.min).min();
+        self.max = self.max.into_iter().chain(other.max).max();
+    }
+}
+
+impl FromIterator<u64> for Aggregate {
+    fn from_iter<I: IntoIterator<Item = u64>>(values: I) -> Self {
+        let mut aggregate = Aggregate::default();
+        for value in values {
+            aggregate.push(value);
+        }
+        aggregate
+    }
+}
+
+/// Aggregates `count` values written by [`crate::block::encode`] into `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn blocks(input: &[u8], count: usize, out: &mut Aggregate) -> Result<usize> {
+    let kernel = Kernel::detect();
+    let mut pos = 0;
+    for first in (0..count).step_by(BLOCK_LEN) {
+        let len = (count - first).min(BLOCK_LEN);
+        let &width = input.get(pos).ok_or(Error::Truncated)?;
+        pos += 1;
+        pos += add_block(kernel, 0, 32, width, &input[pos..], len, out)?;
+    }
+    Ok(pos)
+}
+
+/// Aggregates `count` values written by [`crate::frame_of_ref::encode`] into
+/// `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn frame_of_ref(input: &[u8], count: usize, out: &mut Aggregate) -> Result<usize> {
+    let kernel = Kernel::detect();
+    let mut pos = 0;
+    for first in (0..count).step_by(BLOCK_LEN) {
+        let len = (count - first).min(BLOCK_LEN);
+        let header = input.get(pos..pos + 5).ok_or(Error::Truncated)?;
+        let min = u32::from_le_bytes(header[..4].try_into().unwrap());
+        pos += 5;
+        pos += add_block(kernel, min as u64, 32, header[4], &input[pos..], len, out)?;
+    }
+    Ok(pos)
+}
+
+/// 64-bit variant of [`frame_of_ref`] for [`crate::frame_of_ref::encode64`].
+pub fn frame_of_ref64(input: &[u8], count: usize, out: &mut Aggregate) -> Result<usize> {
+    let kernel = Kernel::detect();
+    let mut pos = 0;
+    for first in (0..count).step_by(BLOCK_LEN) {
+        let len = (count - first).min(BLOCK_LEN);
+        let header = input.get(pos..pos + HEADER_LEN64).ok_or(Error::Truncated)?;
+        let min = u64::from_le_bytes(header[..8].try_into().unwrap());
+        pos += HEADER_LEN64;
+        pos += add_block(kernel, min, 64, header[8], &input[pos..], len, out)?;
+    }
+    Ok(pos)
+}
+
+// Aggregates one block of `len` values `min + r`, wrapped to `value_bits` bits
+// like the decoders do, and returns the payload bytes consumed.
+fn add_block(
+    kernel: Kernel,
+    min: u64,
+    value_bits: u8,
+    width: u8,
+    input: &[u8],
+    len: usize,
+    out: &mut Aggregate,
+) -> Result<usize> {
+    if width > value_bits {
+        return Err(Error::InvalidBitWidth(width));
+    }
+    let limit = u64::MAX >> (64 - value_bits);
+    let bytes = packed_bytes(len, width);
+    let payload = input.get(..bytes).ok_or(Error::Truncated)?;
+
+    // Width 0: every value is the minimum, and there is no payload to read.
+    if width == 0 {
+        out.merge(Aggregate {
+            count: len as u64,
+            sum: min as u128 * len as u128,
+            min: Some(min),
+            max: Some(min),
+        });
+        return Ok(bytes);
+    }
+
+    let mut words = [0u64; BLOCK_LEN];
+    read_bytes(payload, &mut words);
+    let mut residuals = [0u64; BLOCK_LEN];
+    let residuals = &mut residuals[..len];
+    let (sum, lo, hi) = if width <= 32 && len == BLOCK_LEN {
+        let (sum, lo, hi) = kernel.aggregate_block(&words, width);
+        (sum as u128, lo as u64, hi as u64)
+    } else {
+        unpack64(&words, width, residuals);
+        let sum = residuals.iter().map(|&r| r as u128).sum();
+        let lo = residuals.iter().copied().min().unwrap_or(0);
+        let hi = residuals.iter().copied().max().unwrap_or(0);
+        (sum, lo, hi)
+    };
+
+    match min.checked_add(hi).filter(|&top| top <= limit) {
+        Some(top) => out.merge(Aggregate {
+            count: len as u64,
+            sum: min as u128 * len as u128 + sum,
+            min: Some(min + lo),
+            max: Some(top),
+        }),
+        // Only corrupt input wraps; fold the wrapped values one by one.
+        None => {
+            unpack64(&words, width, residuals);
+            for &r in residuals.iter() {
+                out.push(r.wrapping_add(min) & limit);
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+// Scalar reference: unpack, then fold.
+pub(crate) fn aggregate_scalar(packed: &[u64], bit_width: u8) -> (u64, u32, u32) {
+    let mut values = [0u32; BLOCK_LEN];
+    unpack(packed, bit_width, &mut values);
+    let sum = values.iter().map(|&v| v as u64).sum();
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    (sum, min, max)
+}
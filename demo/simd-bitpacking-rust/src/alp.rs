@@ -0,0 +1,208 @@
+// ALP: adaptive lossless floating-point compression.
+//
+// Most float metrics are decimals in disguise: 12.5 is 125 / 10. ALP picks an
+// exponent `e` and a factor `f <= e` per vector and turns each value `n` into
+// the integer
+//
+//   d = round(n * 10^e / 10^f)
+//
+// which is then bitpacked with 64-bit frame of reference. Decoding computes
+// `d * 10^f * 10^-e` with the same f64 constants as the encoder. Splitting
+// the scale into two multiplications lets more values round-trip exactly than
+// a single power of ten would, since each pair rounds differently. Any value
+// that does not come back bit for bit, including NaN, infinities and -0.0, is
+// stored separately as an exception; its slot in the integer stream is filled
+// with a neighbour so that it does not widen the block.
+//
+// `e` and `f` are chosen per vector by trying every combination on an evenly
+// spaced sample of the vector and keeping the one with the smallest estimated
+// size. Layout, repeated for every vector of up to 1024 values:
+//
+//   [exponent: u8][factor: u8][exception count: u16 LE]
+//   [64-bit FOR blocks of the integers, see `frame_of_ref`]
+//   [exception positions: u16 LE each][exception values: f64 LE each]
+
+use crate::block::{max_bits64, BLOCK_LEN};
+use crate::error::{Error, Result};
+use crate::frame_of_ref::{decode_block64, encode_block64};
+
+/// Number of values per vector, the unit that shares an exponent and factor.
+pub const VECTOR_LEN: usize = 1024;
+
+/// Largest exponent: 10^18 is the last power of ten an i64 holds.
+pub const MAX_EXPONENT: u8 = 18;
+
+// Values sampled from each vector to choose its exponent and factor.
+const SAMPLE_LEN: usize = 32;
+
+// Encoded integers are kept within f64's exact integer range.
+const ENCODING_LIMIT: f64 = (1u64 << 52) as f64;
+
+// Estimated cost of an exception, in bits: its position and its value.
+const EXCEPTION_BITS: usize = 16 + 64;
+
+const HEADER_LEN: usize = 4;
+
+// Flipping the sign bit maps i64 to u64 preserving order, so that FOR's
+// minimum is the smallest integer and residuals are plain differences.
+const SIGN: u64 = 1 << 63;
+
+#[rustfmt::skip]
+const F10: [f64; MAX_EXPONENT as usize + 1] = [
+    1.0, 10.0, 100.0, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9,
+    1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16, 1e17, 1e18,
+];
+
+#[rustfmt::skip]
+const IF10: [f64; MAX_EXPONENT as usize + 1] = [
+    1.0, 0.1, 0.01, 1e-3, 1e-4, 1e-5, 1e-6, 1e-7, 1e-8, 1e-9,
+    1e-10, 1e-11, 1e-12, 1e-13, 1e-14, 1e-15, 1e-16, 1e-17, 1e-18,
+];
+
+// The integer for `n`, if it is finite and small enough to convert back.
+fn encode_value(n: f64, e: u8, f: u8) -> Option<i64> {
+    let x = (n * F10[e as usize] * IF10[f as usize]).round();
+    // Also false for NaN.
+    (x.abs() < ENCODING_LIMIT).then_some(x as i64)
+}
+
+fn decode_value(d: i64, e: u8, f: u8) -> f64 {
+    d as f64 * F10[f as usize] * IF10[e as usize]
+}
+
+// The integer for `n` if it decodes back to exactly `n`.
+fn exact(n: f64, e: u8, f: u8) -> Option<i64> {
+    encode_value(n, e, f).filter(|&d| decode_value(d, e, f).to_bits() == n.to_bits())
+}
+
+/// Exponent and factor with the smallest estimated encoding of `vector`,
+/// judged on an evenly spaced sample of at most 32 values.
+pub fn choose_exponents(vector: &[f64]) -> (u8, u8) {
+    let step = vector.len().div_ceil(SAMPLE_LEN).max(1);
+    let sample: Vec<f64> = vector.iter().step_by(step).copied().collect();
+
+    let mut best = (0, 0);
+    let mut best_bits = usize::MAX;
+    // Ties go to the larger exponent and factor, as in ALP's reference
+    // implementation.
+    for e in (0..=MAX_EXPONENT).rev() {
+        for f in (0..=e).rev() {
+            let (mut min, mut max, mut exceptions) = (i64::MAX, i64::MIN, 0);
+            for &n in &sample {
+                match exact(n, e, f) {
+                    Some(d) => (min, max) = (min.min(d), max.max(d)),
+                    None => exceptions += 1,
+                }
+            }
+            let range = if min <= max { max.abs_diff(min) } else { 0 };
+            let width = max_bits64(&[range]) as usize;
+            let bits = sample.len() * width + exceptions * EXCEPTION_BITS;
+            if bits < best_bits {
+                (best, best_bits) = ((e, f), bits);
+            }
+        }
+    }
+    best
+}
+
+/// Appends `values` to `out` as ALP vectors.
+pub fn encode(values: &[f64], out: &mut Vec<u8>) {
+    for vector in values.chunks(VECTOR_LEN) {
+        encode_vector(vector, out);
+    }
+}
+
+fn encode_vector(vector: &[f64], out: &mut Vec<u8>) {
+    let (e, f) = choose_exponents(vector);
+    let mut ints = [0i64; VECTOR_LEN];
+    let mut is_exception = [false; VECTOR_LEN];
+    let mut exceptions = Vec::new();
+    for (i, &n) in vector.iter().enumerate() {
+        match exact(n, e, f) {
+            Some(d) => ints[i] = d,
+            None => {
+                is_exception[i] = true;
+                exceptions.push(i);
+            }
+        }
+    }
+
+    // Fill each exception's slot with a value from its own block, so that the
+    // block's range stays the same.
+    let blocks = ints[..vector.len()].chunks_mut(BLOCK_LEN);
+    for (block, is_exception) in blocks.zip(is_exception.chunks(BLOCK_LEN)) {
+        let fill = block.iter().zip(is_exception).find(|(_, &x)| !x);
+        let fill = fill.map_or(0, |(&d, _)| d);
+        for (d, _) in block.iter_mut().zip(is_exception).filter(|(_, &x)| x) {
+            *d = fill;
+        }
+    }
+
+    out.extend_from_slice(&[e, f]);
+    out.extend_from_slice(&(exceptions.len() as u16).to_le_bytes());
+    let mut unsigned = [0u64; BLOCK_LEN];
+    for block in ints[..vector.len()].chunks(BLOCK_LEN) {
+        for (u, &d) in unsigned.iter_mut().zip(block) {
+            *u = d as u64 ^ SIGN;
+        }
+        encode_block64(&unsigned[..block.len()], out);
+    }
+    for &i in &exceptions {
+        out.extend_from_slice(&(i as u16).to_le_bytes());
+    }
+    for &i in &exceptions {
+        out.extend_from_slice(&vector[i].to_le_bytes());
+    }
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<f64>) -> Result<usize> {
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let len = remaining.min(VECTOR_LEN);
+        pos += decode_vector(&input[pos..], len, out)?;
+        remaining -= len;
+    }
+    Ok(pos)
+}
+
+fn decode_vector(input: &[u8], len: usize, out: &mut Vec<f64>) -> Result<usize> {
+    let header = input.get(..HEADER_LEN).ok_or(Error::Truncated)?;
+    let (e, f) = (header[0], header[1]);
+    if e > MAX_EXPONENT || f > e {
+        return Err(Error::Corrupt("bad exponent or factor"));
+    }
+    let exceptions = u16::from_le_bytes([header[2], header[3]]) as usize;
+    if exceptions > len {
+        return Err(Error::Corrupt("exception count exceeds vector"));
+    }
+
+    let mut pos = HEADER_LEN;
+    let start = out.len();
+    let mut unsigned = [0u64; BLOCK_LEN];
+    for first in (0..len).step_by(BLOCK_LEN) {
+        let block = &mut unsigned[..(len - first).min(BLOCK_LEN)];
+        pos += decode_block64(&input[pos..], block)?;
+        out.extend(block.iter().map(|&u| decode_value((u ^ SIGN) as i64, e, f)));
+    }
+
+    let positions = input
+        .get(pos..pos + 2 * exceptions)
+        .ok_or(Error::Truncated)?;
+    pos += 2 * exceptions;
+    let values = input
+        .get(pos..pos + 8 * exceptions)
+        .ok_or(Error::Truncated)?;
+    pos += 8 * exceptions;
+    for (p, v) in positions.chunks_exact(2).zip(values.chunks_exact(8)) {
+        let p = u16::from_le_bytes([p[0], p[1]]) as usize;
+        if p >= len {
+            return Err(Error::Corrupt("exception position outside vector"));
+        }
+        out[start + p] = f64::from_le_bytes(v.try_into().unwrap());
+    }
+    Ok(pos)
+}
@@ -0,0 +1,400 @@
+// Command-line compressor for numeric CSV columns.
+//
+//   bitpack compress --column ts --codec dod [-o ts.bpak] input.csv
+//   bitpack decompress [-o ts.txt] ts.bpak
+//   bitpack stats [--column ts] input.csv
+//
+// The input is a CSV file with a header row and unquoted numeric fields. A
+// compressed column is a sequence of framed blocks (see `format`) holding up
+// to 65536 values each; decompressing prints one value per line. `stats`
+// compresses each column with every codec that can represent it and reports
+// the ratio against 8 bytes per value. `--codec auto` picks a codec for every
+// block of 4096 values with `adaptive::Selector`.
+
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use simd_bitpacking_demo::format::{Block, Codec};
+use simd_bitpacking_demo::Error;
+use simd_bitpacking_demo::{
+    adaptive, alp, block, chimp, delta, dictionary, dod, frame_of_ref, gorilla, leb128,
+    parquet_rle, pfor, simple8b, streamvbyte,
+};
+
+const USAGE: &str = "\
+usage: bitpack compress --column NAME --codec CODEC [-o OUTPUT] INPUT.csv
+       bitpack decompress [-o OUTPUT] INPUT
+       bitpack stats [--column NAME] INPUT.csv
+
+codecs: bitpack, for, for64, pfor, dod, gorilla, chimp, chimp128, alp,
+        simple8b, leb128, streamvbyte, parquet-rle,
+        dictionary, delta, auto (chosen per 4096 values)
+bitpack, for, pfor, streamvbyte and parquet-rle take values in the u32 range;
+for64, simple8b, leb128, dictionary, delta and auto take unsigned values
+output defaults to stdout";
+
+// Values per framed block.
+const FRAME_LEN: usize = 1 << 16;
+
+// Size of an uncompressed value, the baseline for ratios.
+const RAW_BYTES: usize = 8;
+
+enum Column {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+}
+
+impl Column {
+    fn len(&self) -> usize {
+        match self {
+            Column::Int(values) => values.len(),
+            Column::Float(values) => values.len(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    command: String,
+    column: Option<String>,
+    codec: Option<String>,
+    output: Option<String>,
+    input: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        command: args.next().ok_or("missing command")?,
+        ..Args::default()
+    };
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--column" => &mut parsed.column,
+            "--codec" => &mut parsed.codec,
+            "-o" | "--output" => &mut parsed.output,
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}")),
+            _ if parsed.input.is_none() => {
+                parsed.input = Some(arg);
+                continue;
+            }
+            _ => return Err(format!("unexpected argument {arg}")),
+        };
+        *slot = Some(args.next().ok_or(format!("{arg} needs a value"))?);
+    }
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("bitpack: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("bitpack: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let input = args.input.as_deref().ok_or("missing input file")?;
+    let output = match args.command.as_str() {
+        "compress" => {
+            let name = args.column.as_deref().ok_or("missing --column")?;
+            let codec = args.codec.as_deref().ok_or("missing --codec")?;
+            let codec = match codec {
+                "auto" => None,
+                codec => Some(
+                    Codec::ALL
+                        .into_iter()
+                        .find(|c| c.name() == codec)
+                        .ok_or(format!("unknown codec {codec}"))?,
+                ),
+            };
+            let (headers, rows) = read_csv(input)?;
+            let column = column(&headers, &rows, name)?;
+            match codec {
+                Some(codec) => compress(codec, &column)?,
+                None => compress_auto(&column)?,
+            }
+        }
+        "decompress" => {
+            let bytes = fs::read(input).map_err(|e| format!("{input}: {e}"))?;
+            decompress(&bytes)?.into_bytes()
+        }
+        "stats" => {
+            let (headers, rows) = read_csv(input)?;
+            stats(&headers, &rows, args.column.as_deref())?.into_bytes()
+        }
+        command => return Err(format!("unknown command {command}\n\n{USAGE}")),
+    };
+
+    match args.output {
+        Some(path) => fs::write(&path, output).map_err(|e| format!("{path}: {e}")),
+        None => io::stdout()
+            .write_all(&output)
+            .map_err(|e| format!("stdout: {e}")),
+    }
+}
+
+// A data row's line number in the file, counting from 1, and its fields.
+type Row = (usize, Vec<String>);
+
+// Returns the header and the data rows, with fields split on commas. Blank
+// lines are skipped but still counted in line numbers.
+fn read_csv(path: &str) -> Result<(Vec<String>, Vec<Row>), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let split = |line: &str| line.split(',').map(|f| f.trim().to_string()).collect();
+    let (_, header) = lines.next().ok_or(format!("{path}: no header row"))?;
+    let rows = lines.map(|(index, line)| (index + 1, split(line)));
+    Ok((split(header), rows.collect()))
+}
+
+// Parses a column as integers if every field is one, else as floats.
+fn column(headers: &[String], rows: &[Row], name: &str) -> Result<Column, String> {
+    let index = headers
+        .iter()
+        .position(|h| h == name)
+        .ok_or(format!("no column named {name}"))?;
+    let fields = rows.iter().map(|(line, row)| {
+        let field = row.get(index).map(String::as_str).unwrap_or("");
+        (*line, field)
+    });
+
+    if let Ok(values) = fields.clone().map(|(_, f)| f.parse()).collect() {
+        return Ok(Column::Int(values));
+    }
+    fields
+        .map(|(line, f)| {
+            f.parse()
+                .map_err(|_| format!("column {name}, line {line}: not a number: {f:?}"))
+        })
+        .collect::<Result<_, _>>()
+        .map(Column::Float)
+}
+
+// Encodes a column as framed blocks, or explains why the codec cannot.
+fn compress(codec: Codec, column: &Column) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    match (codec, column) {
+        (Codec::Dod, Column::Int(values)) => frames(codec, values, &mut out, |frame, payload| {
+            dod::encode(frame, payload).map_err(|e| format!("dod: {e}"))?;
+            Ok(0)
+        })?,
+        (Codec::Gorilla | Codec::Chimp | Codec::Chimp128 | Codec::Alp, Column::Float(values)) => {
+            let encode = match codec {
+                Codec::Gorilla => gorilla::encode,
+                Codec::Chimp => chimp::encode,
+                Codec::Alp => alp::encode,
+                _ => chimp::encode128,
+            };
+            frames(codec, values, &mut out, |frame, payload| {
+                encode(frame, payload);
+                Ok(0)
+            })?
+        }
+        (Codec::Gorilla | Codec::Chimp | Codec::Chimp128 | Codec::Alp, Column::Int(values)) => {
+            // Integers up to 2^53 in magnitude convert to f64 exactly.
+            let floats = convert(values, |v| {
+                (v.unsigned_abs() <= 1 << 53).then_some(v as f64)
+            })
+            .ok_or(format!("{}: values exceed f64 precision", codec.name()))?;
+            return compress(codec, &Column::Float(floats));
+        }
+        (
+            Codec::Bitpack | Codec::For | Codec::Pfor | Codec::StreamVByte | Codec::ParquetRle,
+            Column::Int(values),
+        ) => {
+            let values = convert(values, |v| u32::try_from(v).ok().map(u64::from))
+                .ok_or(format!("{}: values outside the u32 range", codec.name()))?;
+            integer_frames(codec, &values, &mut out)?
+        }
+        (Codec::For64 | Codec::Dictionary | Codec::Delta, Column::Int(values)) => {
+            let values = convert(values, |v| u64::try_from(v).ok())
+                .ok_or(format!("{}: negative values", codec.name()))?;
+            integer_frames(codec, &values, &mut out)?
+        }
+        (Codec::Simple8b, Column::Int(values)) => {
+            let values =
+                convert(values, |v| u64::try_from(v).ok()).ok_or("simple8b: negative values")?;
+            frames(codec, &values, &mut out, |frame, payload| {
+                simple8b::encode(frame, payload).map_err(|e| format!("simple8b: {e}"))?;
+                Ok(0)
+            })?
+        }
+        (Codec::Leb128, Column::Int(values)) => {
+            let values =
+                convert(values, |v| u64::try_from(v).ok()).ok_or("leb128: negative values")?;
+            frames(codec, &values, &mut out, |frame, payload| {
+                leb128::encode(frame, payload);
+                Ok(0)
+            })?
+        }
+        (_, Column::Float(_)) => return Err(format!("{}: needs integer values", codec.name())),
+    }
+    Ok(out)
+}
+
+// Encodes a column with the codec the selector picks for each block.
+fn compress_auto(column: &Column) -> Result<Vec<u8>, String> {
+    let Column::Int(values) = column else {
+        return Err("auto: needs integer values".to_string());
+    };
+    let values = convert(values, |v| u64::try_from(v).ok()).ok_or("auto: negative values")?;
+    let mut out = Vec::new();
+    adaptive::Selector::new().encode(&values, &mut out);
+    Ok(out)
+}
+
+fn convert<T: Copy, U>(values: &[T], f: impl Fn(T) -> Option<U>) -> Option<Vec<U>> {
+    values.iter().map(|&v| f(v)).collect()
+}
+
+// Frames `values` in chunks of `FRAME_LEN`. `encode` appends the payload for a
+// chunk and returns the bit width to record in its header.
+fn frames<T>(
+    codec: Codec,
+    values: &[T],
+    out: &mut Vec<u8>,
+    encode: impl Fn(&[T], &mut Vec<u8>) -> Result<u8, String>,
+) -> Result<(), String> {
+    let mut payload = Vec::new();
+    for frame in values.chunks(FRAME_LEN) {
+        payload.clear();
+        let bit_width = encode(frame, &mut payload)?;
+        let block = Block {
+            codec,
+            bit_width,
+            count: frame.len() as u32,
+            payload: &payload,
+        };
+        block.write(out);
+    }
+    Ok(())
+}
+
+// Frames `values` in chunks of `FRAME_LEN`, each with the header width the
+// codec packs it at.
+fn integer_frames(codec: Codec, values: &[u64], out: &mut Vec<u8>) -> Result<(), String> {
+    let mut payload = Vec::new();
+    for frame in values.chunks(FRAME_LEN) {
+        adaptive::encode_block(codec, frame, &mut payload)
+            .ok_or(format!("{}: values do not fit the codec", codec.name()))?
+            .write(out);
+    }
+    Ok(())
+}
+
+// Decodes every framed block and prints one value per line.
+fn decompress(mut bytes: &[u8]) -> Result<String, String> {
+    let mut out = String::new();
+    while !bytes.is_empty() {
+        let (frame, len) = Block::read(bytes)
+            .and_then(|(frame, len)| match frame.count as usize {
+                // The writer never frames more, and RLE runs or width-0
+                // blocks would otherwise expand a few bytes without bound.
+                count if count > FRAME_LEN => Err(Error::Corrupt("block exceeds frame length")),
+                _ => Ok((frame, len)),
+            })
+            .map_err(|e| format!("bad block: {e}"))?;
+        let (payload, count) = (frame.payload, frame.count as usize);
+        let read = match frame.codec {
+            Codec::Bitpack => decode_lines(payload, count, block::decode, &mut out),
+            Codec::For => decode_lines(payload, count, frame_of_ref::decode, &mut out),
+            Codec::For64 => decode_lines(payload, count, frame_of_ref::decode64, &mut out),
+            Codec::Pfor => decode_lines(payload, count, pfor::decode, &mut out),
+            Codec::Dod => decode_lines(payload, count, dod::decode, &mut out),
+            Codec::Gorilla => decode_lines(payload, count, gorilla::decode, &mut out),
+            Codec::Chimp => decode_lines(payload, count, chimp::decode, &mut out),
+            Codec::Chimp128 => decode_lines(payload, count, chimp::decode128, &mut out),
+            Codec::Alp => decode_lines(payload, count, alp::decode, &mut out),
+            Codec::Simple8b => decode_lines(payload, count, simple8b::decode, &mut out),
+            Codec::Leb128 => decode_lines(payload, count, leb128::decode, &mut out),
+            Codec::StreamVByte => decode_lines(payload, count, streamvbyte::decode, &mut out),
+            Codec::ParquetRle => decode_lines(payload, count, parquet_rle::decode, &mut out),
+            Codec::Dictionary => decode_lines(payload, count, dictionary::decode, &mut out),
+            Codec::Delta => decode_lines(payload, count, delta::decode_series, &mut out),
+        };
+        let read = read.map_err(|e| format!("{} block: {e}", frame.codec.name()))?;
+        if read != payload.len() {
+            return Err(format!("{} block: trailing bytes", frame.codec.name()));
+        }
+        bytes = &bytes[len..];
+    }
+    Ok(out)
+}
+
+fn decode_lines<T: Display>(
+    payload: &[u8],
+    count: usize,
+    decode: fn(&[u8], usize, &mut Vec<T>) -> simd_bitpacking_demo::Result<usize>,
+    out: &mut String,
+) -> simd_bitpacking_demo::Result<usize> {
+    let mut values = Vec::with_capacity(count.min(FRAME_LEN));
+    let read = decode(payload, count, &mut values)?;
+    for v in values {
+        out.push_str(&v.to_string());
+        out.push('\n');
+    }
+    Ok(read)
+}
+
+// One table per column: compressed size, ratio and bits per value by codec.
+fn stats(headers: &[String], rows: &[Row], only: Option<&str>) -> Result<String, String> {
+    let names: Vec<&str> = match only {
+        Some(name) => vec![name],
+        None => headers.iter().map(String::as_str).collect(),
+    };
+
+    let mut out = String::new();
+    for name in names {
+        let column = match column(headers, rows, name) {
+            Ok(column) => column,
+            // Without --column, skip the columns that are not numeric.
+            Err(_) if only.is_none() => continue,
+            Err(e) => return Err(e),
+        };
+        let raw = column.len() * RAW_BYTES;
+        out += &format!("{name}: {} values, {raw} bytes raw\n", column.len());
+        out += &format!(
+            "  {:<11} {:>12} {:>8} {:>10}\n",
+            "codec", "bytes", "ratio", "bits/value"
+        );
+        let results = Codec::ALL
+            .into_iter()
+            .map(|codec| (codec.name(), compress(codec, &column)))
+            .chain([("auto", compress_auto(&column))]);
+        for (codec, result) in results {
+            match result {
+                Ok(bytes) => {
+                    // An empty column compresses to nothing, which has no ratio.
+                    let ratio = match bytes.len() {
+                        0 => "-".to_string(),
+                        len => format!("{:.2}", raw as f64 / len as f64),
+                    };
+                    let bits = (bytes.len() * 8) as f64 / column.len().max(1) as f64;
+                    out += &format!(
+                        "  {:<11} {:>12} {:>8} {:>10.2}\n",
+                        codec,
+                        bytes.len(),
+                        ratio,
+                        bits
+                    );
+                }
+                Err(reason) => out += &format!("  {:<11} {:>12} ({reason})\n", codec, "-"),
+            }
+        }
+    }
+    Ok(out)
+}
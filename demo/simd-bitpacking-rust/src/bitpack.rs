@@ -0,0 +1,174 @@
+// Scalar bit-packing over a little-endian stream of u64 words.
+//
+// Value `i` occupies bits `[i * bit_width, (i + 1) * bit_width)` of the stream,
+// counted from the least significant bit of `out[0]`. A value whose bits do not
+// fit in the remainder of a word continues in the low bits of the next word.
+
+/// Number of `u64` words needed to hold `count` values of `bit_width` bits.
+pub fn packed_len(count: usize, bit_width: u8) -> usize {
+    (count * bit_width as usize).div_ceil(64)
+}
+
+// Low `bit_width` bits set. `1 << 64` overflows, so width 64 is special.
+pub(crate) fn mask(bit_width: u8) -> u64 {
+    match bit_width {
+        0 => 0,
+        w => u64::MAX >> (64 - w),
+    }
+}
+
+/// Packs `values` into `out` using `bit_width` bits per value.
+///
+/// Bits above `bit_width` are ignored. The first `packed_len(values.len(),
+/// bit_width)` words of `out` are overwritten; the rest are left untouched.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 32` or if `out` is shorter than `packed_len`.
+pub fn pack(values: &[u32], bit_width: u8, out: &mut [u64]) {
+    assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+    pack_words(values, bit_width, out);
+}
+
+/// Unpacks `out.len()` values of `bit_width` bits from `packed`.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 32` or if `packed` is shorter than
+/// `packed_len(out.len(), bit_width)`.
+pub fn unpack(packed: &[u64], bit_width: u8, out: &mut [u32]) {
+    assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+    unpack_words(packed, bit_width, out, |v| v as u32);
+}
+
+/// 64-bit variant of [`pack`] for widths up to 64.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 64` or if `out` is shorter than `packed_len`.
+pub fn pack64(values: &[u64], bit_width: u8, out: &mut [u64]) {
+    assert!(bit_width <= 64, "bit width {bit_width} exceeds 64");
+    pack_words(values, bit_width, out);
+}
+
+/// 64-bit variant of [`unpack`] for widths up to 64.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 64` or if `packed` is shorter than
+/// `packed_len(out.len(), bit_width)`.
+pub fn unpack64(packed: &[u64], bit_width: u8, out: &mut [u64]) {
+    assert!(bit_width <= 64, "bit width {bit_width} exceeds 64");
+    unpack_words(packed, bit_width, out, |v| v);
+}
+
+/// Returns value `i` of a stream packed at `bit_width`, touching only the
+/// one or two words that hold its bits.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 32` or if value `i` lies beyond `packed`.
+pub fn get(packed: &[u64], bit_width: u8, i: usize) -> u32 {
+    assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+    get_word(packed, bit_width, i) as u32
+}
+
+/// 64-bit variant of [`get`] for widths up to 64.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 64` or if value `i` lies beyond `packed`.
+pub fn get64(packed: &[u64], bit_width: u8, i: usize) -> u64 {
+    assert!(bit_width <= 64, "bit width {bit_width} exceeds 64");
+    get_word(packed, bit_width, i)
+}
+
+fn get_word(packed: &[u64], bit_width: u8, i: usize) -> u64 {
+    if bit_width == 0 {
+        return 0;
+    }
+    let width = bit_width as usize;
+    let bit = i * width;
+    let (word, shift) = (bit / 64, bit % 64);
+    let mut v = packed[word] >> shift;
+    if shift + width > 64 {
+        v |= packed[word + 1] << (64 - shift);
+    }
+    v & mask(bit_width)
+}
+
+fn pack_words<T: Copy + Into<u64>>(values: &[T], bit_width: u8, out: &mut [u64]) {
+    let words = packed_len(values.len(), bit_width);
+    assert!(
+        out.len() >= words,
+        "output holds {} words, need {words}",
+        out.len()
+    );
+
+    out[..words].fill(0);
+    if bit_width == 0 {
+        return;
+    }
+
+    let width = bit_width as usize;
+    let mask = mask(bit_width);
+    for (i, &v) in values.iter().enumerate() {
+        let v = v.into() & mask;
+        let bit = i * width;
+        let (word, shift) = (bit / 64, bit % 64);
+        out[word] |= v << shift;
+        // The value straddles a word boundary: spill the high bits. A spill
+        // needs `shift > 0`, so the shift below stays under 64.
+        if shift + width > 64 {
+            out[word + 1] |= v >> (64 - shift);
+        }
+    }
+}
+
+fn unpack_words<T>(packed: &[u64], bit_width: u8, out: &mut [T], cast: impl Fn(u64) -> T) {
+    let words = packed_len(out.len(), bit_width);
+    assert!(
+        packed.len() >= words,
+        "input holds {} words, need {words}",
+        packed.len()
+    );
+
+    if bit_width == 0 {
+        out.iter_mut().for_each(|slot| *slot = cast(0));
+        return;
+    }
+
+    let width = bit_width as usize;
+    let mask = mask(bit_width);
+    for (i, slot) in out.iter_mut().enumerate() {
+        let bit = i * width;
+        let (word, shift) = (bit / 64, bit % 64);
+        let mut v = packed[word] >> shift;
+        if shift + width > 64 {
+            v |= packed[word + 1] << (64 - shift);
+        }
+        *slot = cast(v & mask);
+    }
+}
+
+/// Number of bytes needed to hold `count` values of `bit_width` bits.
+pub(crate) fn packed_bytes(count: usize, bit_width: u8) -> usize {
+    (count * bit_width as usize).div_ceil(8)
+}
+
+// Appends the first `len` bytes of the little-endian image of `words`.
+pub(crate) fn write_bytes(words: &[u64], len: usize, out: &mut Vec<u8>) {
+    for (i, word) in words.iter().enumerate().take(len.div_ceil(8)) {
+        let take = (len - i * 8).min(8);
+        out.extend_from_slice(&word.to_le_bytes()[..take]);
+    }
+}
+
+// Loads little-endian `bytes` into `words`, zero-filling the last partial word.
+pub(crate) fn read_bytes(bytes: &[u8], words: &mut [u64]) {
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        *word = u64::from_le_bytes(buf);
+    }
+}
@@ -0,0 +1,186 @@
+// MSB-first bit streams over byte buffers, as used by Gorilla-style codecs.
+//
+// The first bit written is the most significant bit of the first byte, so a
+// stream reads left to right the way the papers draw it.
+//
+// Both sides work a 64-bit register at a time instead of a bit at a time: the
+// writer collects bits in an accumulator and emits it as 8 big-endian bytes
+// once full, and the reader loads 8 bytes with a single big-endian read and
+// tops its register up from them.
+
+use crate::error::{Error, Result};
+
+/// Appends variable-width bit fields to a byte buffer.
+#[derive(Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    // Pending bits, left-aligned: the next bit goes below the top `filled`.
+    acc: u64,
+    filled: u32,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(bytes: usize) -> Self {
+        BitWriter {
+            bytes: Vec::with_capacity(bytes),
+            ..Self::default()
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        self.write_bits(bit as u64, 1);
+    }
+
+    /// Writes the low `n` bits of `value`, most significant first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    pub fn write_bits(&mut self, value: u64, n: u32) {
+        assert!(n <= 64, "cannot write {n} bits at once");
+        if n == 0 {
+            return;
+        }
+        let value = if n < 64 {
+            value & ((1 << n) - 1)
+        } else {
+            value
+        };
+
+        let free = 64 - self.filled;
+        if n < free {
+            self.acc |= value << (free - n);
+            self.filled += n;
+        } else {
+            // Top up the accumulator, emit it, and keep the leftover bits.
+            self.acc |= value >> (n - free);
+            self.bytes.extend_from_slice(&self.acc.to_be_bytes());
+            let rest = n - free;
+            self.acc = if rest == 0 { 0 } else { value << (64 - rest) };
+            self.filled = rest;
+        }
+    }
+
+    /// Pads the stream with zero bits up to the next byte boundary and moves
+    /// all pending bits into the buffer.
+    pub fn flush(&mut self) {
+        let bytes = self.filled.div_ceil(8) as usize;
+        self.bytes
+            .extend_from_slice(&self.acc.to_be_bytes()[..bytes]);
+        self.acc = 0;
+        self.filled = 0;
+    }
+
+    /// Number of bits written so far.
+    pub fn len_bits(&self) -> usize {
+        self.bytes.len() * 8 + self.filled as usize
+    }
+
+    /// Flushes and returns the stream.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.flush();
+        self.bytes
+    }
+}
+
+/// Reads variable-width bit fields from a byte buffer.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    // Next byte not yet accounted for in `avail`.
+    next: usize,
+    // Buffered bits, left-aligned. Bits below the top `avail` are either zero
+    // or a copy of the bits that follow, so they can be shifted up freely.
+    buf: u64,
+    avail: u32,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BitReader {
+            bytes,
+            next: 0,
+            buf: 0,
+            avail: 0,
+        }
+    }
+
+    // Tops the register up to at least 57 bits, or to the end of the input.
+    fn refill(&mut self) {
+        if self.avail > 56 {
+            return;
+        }
+        if let Some(chunk) = self.bytes.get(self.next..self.next + 8) {
+            let word = u64::from_be_bytes(chunk.try_into().unwrap());
+            self.buf |= word >> self.avail;
+            let whole = (64 - self.avail) / 8;
+            self.next += whole as usize;
+            self.avail += whole * 8;
+        } else {
+            while self.avail <= 56 && self.next < self.bytes.len() {
+                self.buf |= (self.bytes[self.next] as u64) << (56 - self.avail);
+                self.next += 1;
+                self.avail += 8;
+            }
+        }
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool> {
+        Ok(self.read_bits(1)? == 1)
+    }
+
+    /// Reads `n` bits, most significant first. Nothing is consumed on error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    pub fn read_bits(&mut self, n: u32) -> Result<u64> {
+        assert!(n <= 64, "cannot read {n} bits at once");
+        if n > 56 {
+            // The register guarantees only 57 bits after a refill.
+            if (n as usize) > self.remaining() {
+                return Err(Error::Truncated);
+            }
+            let hi = self.read_bits(n - 32)?;
+            let lo = self.read_bits(32)?;
+            return Ok(hi << 32 | lo);
+        }
+        let value = self.peek(n)?;
+        self.buf <<= n;
+        self.avail -= n;
+        Ok(value)
+    }
+
+    /// Returns the next `n` bits without consuming them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 56`.
+    pub fn peek(&mut self, n: u32) -> Result<u64> {
+        assert!(n <= 56, "cannot peek {n} bits at once");
+        if n == 0 {
+            return Ok(0);
+        }
+        if n > self.avail {
+            self.refill();
+            if n > self.avail {
+                return Err(Error::Truncated);
+            }
+        }
+        Ok(self.buf >> (64 - n))
+    }
+
+    /// Number of bits left to read.
+    pub fn remaining(&self) -> usize {
+        self.avail as usize + (self.bytes.len() - self.next) * 8
+    }
+
+    /// Bytes touched so far, counting a partially read byte.
+    pub fn bytes_read(&self) -> usize {
+        (self.bytes.len() * 8 - self.remaining()).div_ceil(8)
+    }
+}
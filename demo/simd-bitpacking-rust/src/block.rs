@@ -0,0 +1,140 @@
+// Block encoding with per-block bit width selection (the "max-bits win").
+//
+// Layout, repeated for every block of up to 128 values:
+//
+//   [bit width: u8][packed values: ceil(len * width / 8) bytes]
+//
+// Only the last block may hold fewer than 128 values, so the decoder needs
+// the total value count to know where the stream ends.
+
+use crate::bitpack::{get, pack, packed_bytes, read_bytes, unpack, write_bytes};
+use crate::error::{Error, Result};
+use crate::kernel::{pack_block, unpack_block};
+
+/// Number of values per block.
+pub const BLOCK_LEN: usize = 128;
+
+// u64 words needed for a full block at the widest width.
+pub(crate) const BLOCK_WORDS: usize = BLOCK_LEN * 32 / 64;
+
+/// Minimal bit width that represents every value in `values`.
+///
+/// ORing the values together keeps the highest set bit of any of them, so a
+/// single `leading_zeros` gives the width without a branch per value.
+pub fn max_bits(values: &[u32]) -> u8 {
+    let or = values.iter().fold(0, |acc, &v| acc | v);
+    (32 - or.leading_zeros()) as u8
+}
+
+/// 64-bit variant of [`max_bits`].
+pub fn max_bits64(values: &[u64]) -> u8 {
+    let or = values.iter().fold(0, |acc, &v| acc | v);
+    (64 - or.leading_zeros()) as u8
+}
+
+/// Appends `values` to `out` as width-prefixed blocks.
+pub fn encode(values: &[u32], out: &mut Vec<u8>) {
+    for block in values.chunks(BLOCK_LEN) {
+        encode_block(block, out);
+    }
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u32>) -> Result<usize> {
+    let mut values = [0u32; BLOCK_LEN];
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let len = remaining.min(BLOCK_LEN);
+        pos += decode_block(&input[pos..], &mut values[..len])?;
+        out.extend_from_slice(&values[..len]);
+        remaining -= len;
+    }
+    Ok(pos)
+}
+
+/// Decodes values `start..start + len` of a `count`-value stream written by
+/// [`encode`] and appends them to `out`.
+///
+/// Blocks before the range are skipped by their width byte alone, and only the
+/// requested values are extracted from the blocks that overlap it.
+///
+/// # Panics
+///
+/// Panics if the range extends past `count`.
+pub fn decode_range(
+    input: &[u8],
+    count: usize,
+    start: usize,
+    len: usize,
+    out: &mut Vec<u32>,
+) -> Result<()> {
+    let end = start.checked_add(len).filter(|&end| end <= count);
+    let end = end.unwrap_or_else(|| panic!("range {start}+{len} exceeds {count} values"));
+
+    let mut words = [0u64; BLOCK_WORDS];
+    let mut pos = 0;
+    for first in (0..end).step_by(BLOCK_LEN) {
+        let block_len = (count - first).min(BLOCK_LEN);
+        let &width = input.get(pos).ok_or(Error::Truncated)?;
+        if width > 32 {
+            return Err(Error::InvalidBitWidth(width));
+        }
+        let bytes = packed_bytes(block_len, width);
+        let payload = input
+            .get(pos + 1..pos + 1 + bytes)
+            .ok_or(Error::Truncated)?;
+        pos += 1 + bytes;
+
+        let (lo, hi) = (start.max(first), end.min(first + block_len));
+        if lo < hi {
+            words.fill(0);
+            read_bytes(payload, &mut words);
+            out.extend((lo..hi).map(|i| get(&words, width, i - first)));
+        }
+    }
+    Ok(())
+}
+
+// Appends one block of at most `BLOCK_LEN` values: width byte, then payload.
+pub(crate) fn encode_block(block: &[u32], out: &mut Vec<u8>) {
+    let width = max_bits(block);
+    out.push(width);
+    write_packed(block, width, out);
+}
+
+// Decodes one block of `out.len()` values, returning the bytes consumed.
+pub(crate) fn decode_block(input: &[u8], out: &mut [u32]) -> Result<usize> {
+    let &width = input.first().ok_or(Error::Truncated)?;
+    Ok(1 + read_packed(&input[1..], width, out)?)
+}
+
+// Appends the payload of one block packed at `width`, without a header.
+pub(crate) fn write_packed(block: &[u32], width: u8, out: &mut Vec<u8>) {
+    let mut words = [0u64; BLOCK_WORDS];
+    match block.try_into() {
+        Ok(full) => pack_block(full, width, &mut words),
+        Err(_) => pack(block, width, &mut words),
+    }
+    write_bytes(&words, packed_bytes(block.len(), width), out);
+}
+
+// Reads the payload of `out.len()` values packed at `width`, returning the
+// bytes consumed.
+pub(crate) fn read_packed(input: &[u8], width: u8, out: &mut [u32]) -> Result<usize> {
+    if width > 32 {
+        return Err(Error::InvalidBitWidth(width));
+    }
+
+    let bytes = packed_bytes(out.len(), width);
+    let payload = input.get(..bytes).ok_or(Error::Truncated)?;
+    let mut words = [0u64; BLOCK_WORDS];
+    read_bytes(payload, &mut words);
+    match out.try_into() {
+        Ok(full) => unpack_block(&words, width, full),
+        Err(_) => unpack(&words, width, out),
+    }
+    Ok(bytes)
+}
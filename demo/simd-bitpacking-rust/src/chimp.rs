@@ -0,0 +1,422 @@
+// Chimp and Chimp128 XOR compression for f64 values.
+//
+// Chimp refines Gorilla's XOR scheme for real-world floats, whose XORs rarely
+// end in long runs of zeros. Trailing zeros only get their own record when
+// there are more than 6 of them; otherwise the XOR is stored down to its last
+// bit and only the leading zeros are described, rounded down to one of eight
+// values so that they fit in 3 bits:
+//
+//   XOR == 0                           '00'
+//   more than 6 trailing zeros         '01' + 3-bit leading zeros
+//                                           + 6-bit meaningful length
+//                                           + meaningful bits
+//   same leading zeros as last record  '10' + bits below the leading zeros
+//   otherwise                          '11' + 3-bit leading zeros
+//                                           + bits below the leading zeros
+//
+// Chimp128 XORs against whichever of the last 128 values shares the current
+// one's low 14 bits, if that leaves more than 13 trailing zeros, and against
+// the previous value otherwise. Records '00' and '01' then carry the 7-bit
+// ring slot of the value they refer to right after the control bits. A hash
+// table keyed by the low bits finds the candidate without scanning the ring.
+//
+// The first value is stored in full. As in `gorilla`, everything works on the
+// raw IEEE 754 bits, so NaN payloads and -0.0 round-trip exactly.
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::{Error, Result};
+
+// Leading zero counts representable in a record, indexed by their 3-bit code.
+const LEADING: [u32; 8] = [0, 8, 12, 16, 18, 20, 22, 24];
+const LEADING_BITS: u32 = 3;
+const LENGTH_BITS: u32 = 6;
+
+// Chimp stores trailing zeros only above this count.
+const THRESHOLD: u32 = 6;
+
+// Chimp128 ring of previous values, and the low bits that index it.
+const RING: usize = 128;
+const RING_BITS: u32 = 7;
+const THRESHOLD128: u32 = THRESHOLD + RING_BITS;
+const KEY_BITS: u32 = THRESHOLD128 + 1;
+
+// Code of the largest representable leading zero count not above `leading`.
+fn leading_code(leading: u32) -> usize {
+    LEADING.iter().rposition(|&l| l <= leading).unwrap()
+}
+
+// Writes the record for a non-first value whose XOR with its reference is
+// `xor`. `slot` is the reference's ring slot, present for Chimp128 only.
+// `leading` holds the leading zeros of the last '11' record, if '10' may
+// reuse them.
+fn write_record(
+    writer: &mut BitWriter,
+    leading: &mut Option<u32>,
+    xor: u64,
+    threshold: u32,
+    slot: Option<usize>,
+) {
+    let write_slot = |writer: &mut BitWriter| {
+        if let Some(slot) = slot {
+            writer.write_bits(slot as u64, RING_BITS);
+        }
+    };
+
+    if xor == 0 {
+        writer.write_bits(0b00, 2);
+        write_slot(writer);
+        *leading = None;
+        return;
+    }
+
+    let code = leading_code(xor.leading_zeros());
+    let lead = LEADING[code];
+    let trailing = xor.trailing_zeros();
+    if trailing > threshold {
+        let len = 64 - lead - trailing;
+        writer.write_bits(0b01, 2);
+        write_slot(writer);
+        writer.write_bits(code as u64, LEADING_BITS);
+        writer.write_bits(len as u64, LENGTH_BITS);
+        writer.write_bits(xor >> trailing, len);
+        *leading = None;
+    } else if *leading == Some(lead) {
+        writer.write_bits(0b10, 2);
+        writer.write_bits(xor, 64 - lead);
+    } else {
+        writer.write_bits(0b11, 2);
+        writer.write_bits(code as u64, LEADING_BITS);
+        writer.write_bits(xor, 64 - lead);
+        *leading = Some(lead);
+    }
+}
+
+// Reads the record for a non-first value. Returns the ring slot of its
+// reference, if the record names one, and the XOR with that reference.
+fn read_record(
+    reader: &mut BitReader,
+    leading: &mut Option<u32>,
+    ring: bool,
+) -> Result<(Option<usize>, u64)> {
+    let read_slot = |reader: &mut BitReader| -> Result<Option<usize>> {
+        if ring {
+            Ok(Some(reader.read_bits(RING_BITS)? as usize))
+        } else {
+            Ok(None)
+        }
+    };
+
+    match reader.read_bits(2)? {
+        0b00 => {
+            *leading = None;
+            Ok((read_slot(reader)?, 0))
+        }
+        0b01 => {
+            let slot = read_slot(reader)?;
+            let lead = LEADING[reader.read_bits(LEADING_BITS)? as usize];
+            let len = reader.read_bits(LENGTH_BITS)? as u32;
+            if len == 0 || lead + len > 64 {
+                return Err(Error::Corrupt("bad meaningful length"));
+            }
+            let trailing = 64 - lead - len;
+            *leading = None;
+            Ok((slot, reader.read_bits(len)? << trailing))
+        }
+        0b10 => {
+            let lead = leading.ok_or(Error::Corrupt("leading zeros reused before being set"))?;
+            Ok((None, reader.read_bits(64 - lead)?))
+        }
+        _ => {
+            let lead = LEADING[reader.read_bits(LEADING_BITS)? as usize];
+            *leading = Some(lead);
+            Ok((None, reader.read_bits(64 - lead)?))
+        }
+    }
+}
+
+/// Streaming Chimp encoder.
+#[derive(Debug, Default)]
+pub struct ChimpEncoder {
+    writer: BitWriter,
+    prev: u64,
+    leading: Option<u32>,
+    count: usize,
+}
+
+impl ChimpEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one value to the stream.
+    pub fn push(&mut self, value: f64) {
+        let x = value.to_bits();
+        if self.count == 0 {
+            self.writer.write_bits(x, 64);
+        } else {
+            write_record(
+                &mut self.writer,
+                &mut self.leading,
+                x ^ self.prev,
+                THRESHOLD,
+                None,
+            );
+        }
+        self.prev = x;
+        self.count += 1;
+    }
+
+    /// Number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the encoded stream, zero-padded to a whole byte.
+    pub fn finish(self) -> Vec<u8> {
+        self.writer.finish()
+    }
+}
+
+/// Decoder for a stream written by [`ChimpEncoder`].
+///
+/// The stream does not record its length, so the caller supplies the number
+/// of values. Iteration stops after that many values or at the first error.
+pub struct ChimpDecoder<'a> {
+    reader: BitReader<'a>,
+    remaining: usize,
+    first: bool,
+    prev: u64,
+    leading: Option<u32>,
+}
+
+impl<'a> ChimpDecoder<'a> {
+    pub fn new(input: &'a [u8], count: usize) -> Self {
+        ChimpDecoder {
+            reader: BitReader::new(input),
+            remaining: count,
+            first: true,
+            prev: 0,
+            leading: None,
+        }
+    }
+
+    /// Input bytes consumed so far.
+    pub fn bytes_read(&self) -> usize {
+        self.reader.bytes_read()
+    }
+
+    fn next_bits(&mut self) -> Result<u64> {
+        if self.first {
+            return self.reader.read_bits(64);
+        }
+        let (_, xor) = read_record(&mut self.reader, &mut self.leading, false)?;
+        Ok(self.prev ^ xor)
+    }
+}
+
+impl Iterator for ChimpDecoder<'_> {
+    type Item = Result<f64>;
+
+    fn next(&mut self) -> Option<Result<f64>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match self.next_bits() {
+            Ok(x) => {
+                self.prev = x;
+                self.first = false;
+                self.remaining -= 1;
+                Some(Ok(f64::from_bits(x)))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Streaming Chimp128 encoder.
+#[derive(Debug)]
+pub struct Chimp128Encoder {
+    writer: BitWriter,
+    // Value `i` lives in slot `i % RING`.
+    ring: [u64; RING],
+    // For each key (low `KEY_BITS` bits), one plus the index of the last value
+    // with that key, or 0 if there was none.
+    last_seen: Vec<usize>,
+    leading: Option<u32>,
+    count: usize,
+}
+
+impl Default for Chimp128Encoder {
+    fn default() -> Self {
+        Chimp128Encoder {
+            writer: BitWriter::new(),
+            ring: [0; RING],
+            last_seen: vec![0; 1 << KEY_BITS],
+            leading: None,
+            count: 0,
+        }
+    }
+}
+
+impl Chimp128Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one value to the stream.
+    pub fn push(&mut self, value: f64) {
+        let x = value.to_bits();
+        let key = (x & ((1 << KEY_BITS) - 1)) as usize;
+        if self.count == 0 {
+            self.writer.write_bits(x, 64);
+        } else {
+            let mut slot = (self.count - 1) % RING;
+            let mut xor = x ^ self.ring[slot];
+            let seen = self.last_seen[key];
+            if seen > 0 && self.count - (seen - 1) <= RING {
+                let candidate = x ^ self.ring[(seen - 1) % RING];
+                if candidate.trailing_zeros() > THRESHOLD128 {
+                    slot = (seen - 1) % RING;
+                    xor = candidate;
+                }
+            }
+            write_record(
+                &mut self.writer,
+                &mut self.leading,
+                xor,
+                THRESHOLD128,
+                Some(slot),
+            );
+        }
+        self.ring[self.count % RING] = x;
+        self.last_seen[key] = self.count + 1;
+        self.count += 1;
+    }
+
+    /// Number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the encoded stream, zero-padded to a whole byte.
+    pub fn finish(self) -> Vec<u8> {
+        self.writer.finish()
+    }
+}
+
+/// Decoder for a stream written by [`Chimp128Encoder`].
+///
+/// The stream does not record its length, so the caller supplies the number
+/// of values. Iteration stops after that many values or at the first error.
+pub struct Chimp128Decoder<'a> {
+    reader: BitReader<'a>,
+    remaining: usize,
+    decoded: usize,
+    ring: [u64; RING],
+    leading: Option<u32>,
+}
+
+impl<'a> Chimp128Decoder<'a> {
+    pub fn new(input: &'a [u8], count: usize) -> Self {
+        Chimp128Decoder {
+            reader: BitReader::new(input),
+            remaining: count,
+            decoded: 0,
+            ring: [0; RING],
+            leading: None,
+        }
+    }
+
+    /// Input bytes consumed so far.
+    pub fn bytes_read(&self) -> usize {
+        self.reader.bytes_read()
+    }
+
+    fn next_bits(&mut self) -> Result<u64> {
+        if self.decoded == 0 {
+            return self.reader.read_bits(64);
+        }
+        let (slot, xor) = read_record(&mut self.reader, &mut self.leading, true)?;
+        let slot = match slot {
+            Some(slot) if slot >= self.decoded => {
+                return Err(Error::Corrupt("reference to an empty ring slot"))
+            }
+            Some(slot) => slot,
+            None => (self.decoded - 1) % RING,
+        };
+        Ok(self.ring[slot] ^ xor)
+    }
+}
+
+impl Iterator for Chimp128Decoder<'_> {
+    type Item = Result<f64>;
+
+    fn next(&mut self) -> Option<Result<f64>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match self.next_bits() {
+            Ok(x) => {
+                self.ring[self.decoded % RING] = x;
+                self.decoded += 1;
+                self.remaining -= 1;
+                Some(Ok(f64::from_bits(x)))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Appends the Chimp stream for `values` to `out`.
+pub fn encode(values: &[f64], out: &mut Vec<u8>) {
+    let mut encoder = ChimpEncoder::new();
+    for &v in values {
+        encoder.push(v);
+    }
+    out.extend_from_slice(&encoder.finish());
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<f64>) -> Result<usize> {
+    let mut decoder = ChimpDecoder::new(input, count);
+    for v in decoder.by_ref() {
+        out.push(v?);
+    }
+    Ok(decoder.bytes_read())
+}
+
+/// Appends the Chimp128 stream for `values` to `out`.
+pub fn encode128(values: &[f64], out: &mut Vec<u8>) {
+    let mut encoder = Chimp128Encoder::new();
+    for &v in values {
+        encoder.push(v);
+    }
+    out.extend_from_slice(&encoder.finish());
+}
+
+/// Decodes `count` values written by [`encode128`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode128(input: &[u8], count: usize, out: &mut Vec<f64>) -> Result<usize> {
+    let mut decoder = Chimp128Decoder::new(input, count);
+    for v in decoder.by_ref() {
+        out.push(v?);
+    }
+    Ok(decoder.bytes_read())
+}
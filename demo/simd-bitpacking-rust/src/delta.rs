@@ -0,0 +1,163 @@
+// Delta and zigzag stage in front of the bitpacker.
+//
+// Each value is replaced by its difference from the previous one, starting
+// from a caller-supplied `base` (usually the last value of the previous block
+// or the first value of the series, stored elsewhere). Sorted series produce
+// non-negative deltas that are packed as-is. Anything else may go backwards,
+// so its deltas are zigzag-mapped (0, -1, 1, -2, ... -> 0, 1, 2, 3, ...) to
+// keep small negative steps small.
+//
+// All arithmetic wraps, so every u64 and i64 series round-trips exactly.
+//
+// `encode_series` stores a whole u64 series with this stage in front of
+// 64-bit frame of reference, using the first value as the base:
+//
+//   [mode: u8, 0 = sorted, 1 = zigzag][base: u64 LE][FOR64 residuals]
+
+use crate::error::{Error, Result};
+use crate::frame_of_ref;
+use crate::kernel::Kernel;
+
+/// How the residuals of a series were produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The series never decreases: residuals are raw deltas.
+    Sorted,
+    /// Residuals are zigzag-encoded deltas.
+    ZigZag,
+}
+
+/// Maps a signed integer to an unsigned one with small magnitudes first.
+pub fn zigzag_encode(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+/// Inverse of [`zigzag_encode`].
+pub fn zigzag_decode(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+/// Writes the residuals of `values`, relative to `base`, into `out`.
+///
+/// # Panics
+///
+/// Panics if `out` is shorter than `values`.
+pub fn encode_u64(base: u64, values: &[u64], out: &mut [u64]) -> Mode {
+    let sorted = values
+        .iter()
+        .try_fold(base, |prev, &v| (v >= prev).then_some(v))
+        .is_some();
+    encode(base, values, sorted, out)
+}
+
+/// Writes the residuals of `values`, relative to `base`, into `out`.
+///
+/// # Panics
+///
+/// Panics if `out` is shorter than `values`.
+pub fn encode_i64(base: i64, values: &[i64], out: &mut [u64]) -> Mode {
+    let sorted = values
+        .iter()
+        .try_fold(base, |prev, &v| (v >= prev).then_some(v))
+        .is_some();
+    // Two's complement makes the wrapping u64 difference of a non-decreasing
+    // i64 pair its true (non-negative) distance.
+    let values = as_unsigned(values);
+    encode(base as u64, values, sorted, out)
+}
+
+fn encode(base: u64, values: &[u64], sorted: bool, out: &mut [u64]) -> Mode {
+    assert!(
+        out.len() >= values.len(),
+        "output holds {} values, need {}",
+        out.len(),
+        values.len()
+    );
+    let mut prev = base;
+    let out = &mut out[..values.len()];
+    if sorted {
+        for (r, &v) in out.iter_mut().zip(values) {
+            *r = v.wrapping_sub(prev);
+            prev = v;
+        }
+        Mode::Sorted
+    } else {
+        for (r, &v) in out.iter_mut().zip(values) {
+            *r = zigzag_encode(v.wrapping_sub(prev) as i64);
+            prev = v;
+        }
+        Mode::ZigZag
+    }
+}
+
+/// Rebuilds values from `residuals` with the fastest kernel the CPU supports.
+///
+/// # Panics
+///
+/// Panics if `out` is shorter than `residuals`.
+pub fn decode_u64(base: u64, residuals: &[u64], mode: Mode, out: &mut [u64]) {
+    Kernel::detect().prefix_sum(base, residuals, mode, out)
+}
+
+/// Rebuilds values from `residuals` with the fastest kernel the CPU supports.
+///
+/// # Panics
+///
+/// Panics if `out` is shorter than `residuals`.
+pub fn decode_i64(base: i64, residuals: &[u64], mode: Mode, out: &mut [i64]) {
+    // SAFETY: i64 and u64 have the same size, alignment and validity.
+    let out = unsafe { std::slice::from_raw_parts_mut(out.as_mut_ptr() as *mut u64, out.len()) };
+    decode_u64(base as u64, residuals, mode, out)
+}
+
+/// Appends `values` to `out` as their first value followed by 64-bit FOR
+/// blocks of the residuals.
+pub fn encode_series(values: &[u64], out: &mut Vec<u8>) {
+    let base = values.first().copied().unwrap_or(0);
+    let mut residuals = vec![0; values.len()];
+    let mode = encode_u64(base, values, &mut residuals);
+    out.push(match mode {
+        Mode::Sorted => 0,
+        Mode::ZigZag => 1,
+    });
+    out.extend_from_slice(&base.to_le_bytes());
+    frame_of_ref::encode64(&residuals, out);
+}
+
+/// Decodes `count` values written by [`encode_series`] and appends them to
+/// `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode_series(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let header = input.get(..9).ok_or(Error::Truncated)?;
+    let mode = match header[0] {
+        0 => Mode::Sorted,
+        1 => Mode::ZigZag,
+        _ => return Err(Error::Corrupt("unknown delta mode")),
+    };
+    let base = u64::from_le_bytes(header[1..].try_into().unwrap());
+    let mut residuals = Vec::new();
+    let read = frame_of_ref::decode64(&input[9..], count, &mut residuals)?;
+    let start = out.len();
+    out.resize(start + count, 0);
+    decode_u64(base, &residuals, mode, &mut out[start..]);
+    Ok(9 + read)
+}
+
+fn as_unsigned(values: &[i64]) -> &[u64] {
+    // SAFETY: i64 and u64 have the same size, alignment and validity.
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u64, values.len()) }
+}
+
+// Scalar running sum; the reference for the SIMD prefix-sum kernels.
+pub(crate) fn prefix_sum(base: u64, residuals: &[u64], mode: Mode, out: &mut [u64]) {
+    let mut acc = base;
+    for (v, &r) in out.iter_mut().zip(residuals) {
+        let delta = match mode {
+            Mode::Sorted => r,
+            Mode::ZigZag => zigzag_decode(r) as u64,
+        };
+        acc = acc.wrapping_add(delta);
+        *v = acc;
+    }
+}
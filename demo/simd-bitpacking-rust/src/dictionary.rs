@@ -0,0 +1,186 @@
+// Dictionary encoding for low-cardinality columns.
+//
+// Every distinct value gets a dense id in order of first appearance, and the
+// column is stored as the dictionary followed by its ids, bitpacked at
+// ceil(log2(dictionary length)) bits with the block kernels. A column of
+// status codes with 5 distinct values costs 3 bits per value plus 40 bytes.
+//
+// A dictionary only pays off while it is small, so once a column has more
+// distinct values than the limit it is written plain instead, like Parquet's
+// fallback from dictionary to plain pages. Layout:
+//
+//   [mode: u8 = 0][values: u64 LE each]                             plain
+//   [mode: u8 = 1][length: u32 LE][entries: u64 LE each]
+//                 [ids: ceil(count * width / 8) bytes]              dictionary
+//
+// The ids are packed in blocks of 128 without headers; the width follows from
+// the dictionary length.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::block::{read_packed, write_packed, BLOCK_LEN};
+use crate::error::{Error, Result};
+
+/// Distinct values a column may have before [`encode`] falls back to plain.
+pub const DEFAULT_MAX_LEN: usize = 1 << 12;
+
+const PLAIN: u8 = 0;
+const DICTIONARY: u8 = 1;
+
+/// Maps distinct values to dense ids, in order of first appearance.
+///
+/// Usable for any hashable value, such as label strings; [`encode`] builds
+/// one over u64 values.
+#[derive(Debug, Clone)]
+pub struct Dictionary<T> {
+    entries: Vec<T>,
+    ids: HashMap<T, u32>,
+}
+
+impl<T> Default for Dictionary<T> {
+    fn default() -> Self {
+        Dictionary {
+            entries: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> Dictionary<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `value`, adding it to the dictionary if it is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dictionary already holds 2^32 entries.
+    pub fn insert(&mut self, value: &T) -> u32 {
+        if let Some(&id) = self.ids.get(value) {
+            return id;
+        }
+        let id = u32::try_from(self.entries.len()).expect("dictionary exceeds 2^32 entries");
+        self.entries.push(value.clone());
+        self.ids.insert(value.clone(), id);
+        id
+    }
+
+    /// The id of `value`, if it is in the dictionary.
+    pub fn id(&self, value: &T) -> Option<u32> {
+        self.ids.get(value).copied()
+    }
+}
+
+impl<T> Dictionary<T> {
+    /// The value with id `id`.
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.entries.get(id as usize)
+    }
+
+    /// Entries in id order.
+    pub fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Bits per id: ceil(log2(len)), and 0 for one entry or none.
+    pub fn bit_width(&self) -> u8 {
+        id_width(self.entries.len())
+    }
+}
+
+fn id_width(len: usize) -> u8 {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()) as u8
+}
+
+/// Appends `values` to `out`, dictionary-encoded if they have at most
+/// [`DEFAULT_MAX_LEN`] distinct values and plain otherwise.
+pub fn encode(values: &[u64], out: &mut Vec<u8>) {
+    encode_with_limit(values, DEFAULT_MAX_LEN, out);
+}
+
+/// Variant of [`encode`] that falls back to plain past `max_len` distinct
+/// values.
+pub fn encode_with_limit(values: &[u64], max_len: usize, out: &mut Vec<u8>) {
+    let mut dictionary = Dictionary::new();
+    let mut ids = Vec::with_capacity(values.len());
+    for v in values {
+        ids.push(dictionary.insert(v));
+        if dictionary.len() > max_len {
+            out.push(PLAIN);
+            for v in values {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            return;
+        }
+    }
+
+    out.push(DICTIONARY);
+    out.extend_from_slice(&(dictionary.len() as u32).to_le_bytes());
+    for entry in dictionary.entries() {
+        out.extend_from_slice(&entry.to_le_bytes());
+    }
+    for block in ids.chunks(BLOCK_LEN) {
+        write_packed(block, dictionary.bit_width(), out);
+    }
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let (&mode, rest) = input.split_first().ok_or(Error::Truncated)?;
+    let read = match mode {
+        PLAIN => {
+            let bytes = count.checked_mul(8).ok_or(Error::Truncated)?;
+            let values = rest.get(..bytes).ok_or(Error::Truncated)?;
+            out.extend(
+                values
+                    .chunks_exact(8)
+                    .map(|v| u64::from_le_bytes(v.try_into().unwrap())),
+            );
+            bytes
+        }
+        DICTIONARY => decode_dictionary(rest, count, out)?,
+        _ => return Err(Error::Corrupt("unknown dictionary mode")),
+    };
+    Ok(1 + read)
+}
+
+fn decode_dictionary(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let len = input.get(..4).ok_or(Error::Truncated)?;
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if len > count {
+        return Err(Error::Corrupt("dictionary longer than the column"));
+    }
+    let mut pos = 4;
+    let entries = input.get(pos..pos + 8 * len).ok_or(Error::Truncated)?;
+    let entries: Vec<u64> = entries
+        .chunks_exact(8)
+        .map(|v| u64::from_le_bytes(v.try_into().unwrap()))
+        .collect();
+    pos += 8 * len;
+
+    let width = id_width(len);
+    let mut ids = [0u32; BLOCK_LEN];
+    for first in (0..count).step_by(BLOCK_LEN) {
+        let ids = &mut ids[..(count - first).min(BLOCK_LEN)];
+        pos += read_packed(&input[pos..], width, ids)?;
+        for &id in ids.iter() {
+            let &value = entries
+                .get(id as usize)
+                .ok_or(Error::Corrupt("id outside dictionary"))?;
+            out.push(value);
+        }
+    }
+    Ok(pos)
+}
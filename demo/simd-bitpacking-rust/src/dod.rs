@@ -0,0 +1,212 @@
+// Delta-of-delta timestamp encoding from the Gorilla paper.
+//
+// Periodic samples have nearly constant deltas, so the difference between
+// consecutive deltas is usually zero or tiny. Each one is stored in the
+// smallest bucket that holds it:
+//
+//   D == 0                 '0'
+//   D in [-64, 63]         '10'   + 7 bits
+//   D in [-256, 255]       '110'  + 9 bits
+//   D in [-2048, 2047]     '1110' + 12 bits
+//   otherwise              '1111' + 32 bits
+//
+// with D in two's complement. The stream starts with the first timestamp in
+// 64 bits and the first delta in 14 bits.
+//
+// Walk-through from the notes, [1643673600, 1643673660, 1643673722, 1643673780]:
+//
+//   t0 = 1643673600            64 bits
+//   t1: delta 60               14 bits
+//   t2: delta 62, D =  2       '10' + 0000010   (9 bits)
+//   t3: delta 58, D = -4       '10' + 1111100   (9 bits)
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::{Error, Result};
+
+const FIRST_DELTA_BITS: u32 = 14;
+
+// (control bits, control length, value bits), narrowest first.
+const BUCKETS: [(u64, u32, u32); 4] = [
+    (0b10, 2, 7),
+    (0b110, 3, 9),
+    (0b1110, 4, 12),
+    (0b1111, 4, 32),
+];
+
+/// Gorilla delta-of-delta encoder for a series of timestamps.
+#[derive(Debug, Default)]
+pub struct TimestampEncoder {
+    writer: BitWriter,
+    prev: i64,
+    prev_delta: i64,
+    count: usize,
+}
+
+impl TimestampEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one timestamp to the stream.
+    ///
+    /// Fails if the first delta is negative or needs more than 14 bits, or if
+    /// a later delta-of-delta does not fit in 32 bits. The stream is left
+    /// unchanged on failure.
+    pub fn push(&mut self, ts: i64) -> Result<()> {
+        match self.count {
+            0 => self.writer.write_bits(ts as u64, 64),
+            1 => {
+                let delta = ts.wrapping_sub(self.prev);
+                if !(0..1 << FIRST_DELTA_BITS).contains(&delta) {
+                    return Err(Error::OutOfRange("first delta must fit in 14 bits"));
+                }
+                self.writer.write_bits(delta as u64, FIRST_DELTA_BITS);
+                self.prev_delta = delta;
+            }
+            _ => {
+                let delta = ts.wrapping_sub(self.prev);
+                let dod = delta.wrapping_sub(self.prev_delta);
+                if dod == 0 {
+                    self.writer.write_bit(false);
+                } else {
+                    let &(control, control_len, bits) = BUCKETS
+                        .iter()
+                        .find(|&&(_, _, bits)| fits(dod, bits))
+                        .ok_or(Error::OutOfRange("delta-of-delta must fit in 32 bits"))?;
+                    self.writer.write_bits(control, control_len);
+                    self.writer.write_bits(dod as u64, bits);
+                }
+                self.prev_delta = delta;
+            }
+        }
+        self.prev = ts;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of timestamps pushed so far.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the encoded stream, zero-padded to a whole byte.
+    pub fn finish(self) -> Vec<u8> {
+        self.writer.finish()
+    }
+}
+
+// Whether `v` is representable in `bits`-bit two's complement.
+fn fits(v: i64, bits: u32) -> bool {
+    let half = 1i64 << (bits - 1);
+    (-half..half).contains(&v)
+}
+
+// Sign-extends the low `bits` bits of `v`.
+fn sign_extend(v: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((v << shift) as i64) >> shift
+}
+
+/// Decoder for a stream written by [`TimestampEncoder`].
+///
+/// The stream does not record its length, so the caller supplies the number
+/// of timestamps. Iteration stops after that many values or at the first
+/// error.
+pub struct TimestampDecoder<'a> {
+    reader: BitReader<'a>,
+    remaining: usize,
+    decoded: usize,
+    prev: i64,
+    prev_delta: i64,
+}
+
+impl<'a> TimestampDecoder<'a> {
+    pub fn new(input: &'a [u8], count: usize) -> Self {
+        TimestampDecoder {
+            reader: BitReader::new(input),
+            remaining: count,
+            decoded: 0,
+            prev: 0,
+            prev_delta: 0,
+        }
+    }
+
+    /// Input bytes consumed so far.
+    pub fn bytes_read(&self) -> usize {
+        self.reader.bytes_read()
+    }
+
+    fn next_delta(&mut self) -> Result<i64> {
+        if self.decoded == 1 {
+            return Ok(self.reader.read_bits(FIRST_DELTA_BITS)? as i64);
+        }
+        // Count leading '1' control bits, up to four.
+        let mut ones = 0;
+        while ones < BUCKETS.len() && self.reader.read_bit()? {
+            ones += 1;
+        }
+        let dod = match ones {
+            0 => 0,
+            n => {
+                let (_, _, bits) = BUCKETS[n - 1];
+                sign_extend(self.reader.read_bits(bits)?, bits)
+            }
+        };
+        Ok(self.prev_delta.wrapping_add(dod))
+    }
+}
+
+impl Iterator for TimestampDecoder<'_> {
+    type Item = Result<i64>;
+
+    fn next(&mut self) -> Option<Result<i64>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let ts = if self.decoded == 0 {
+            self.reader.read_bits(64).map(|v| v as i64)
+        } else {
+            self.next_delta().map(|delta| {
+                self.prev_delta = delta;
+                self.prev.wrapping_add(delta)
+            })
+        };
+        match ts {
+            Ok(ts) => {
+                self.prev = ts;
+                self.decoded += 1;
+                self.remaining -= 1;
+                Some(Ok(ts))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Appends the delta-of-delta stream for `values` to `out`.
+pub fn encode(values: &[i64], out: &mut Vec<u8>) -> Result<()> {
+    let mut encoder = TimestampEncoder::new();
+    for &ts in values {
+        encoder.push(ts)?;
+    }
+    out.extend_from_slice(&encoder.finish());
+    Ok(())
+}
+
+/// Decodes `count` timestamps written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<i64>) -> Result<usize> {
+    let mut decoder = TimestampDecoder::new(input, count);
+    for ts in decoder.by_ref() {
+        out.push(ts?);
+    }
+    Ok(decoder.bytes_read())
+}
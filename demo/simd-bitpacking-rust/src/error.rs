@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Errors reported by the codecs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The input ended before all expected values were read.
+    Truncated,
+    /// A header carried a bit width the codec cannot represent.
+    InvalidBitWidth(u8),
+    /// The input is structurally invalid.
+    Corrupt(&'static str),
+    /// A value cannot be represented by the encoding.
+    OutOfRange(&'static str),
+    /// A block's checksum does not match its contents.
+    ChecksumMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "input is truncated"),
+            Error::InvalidBitWidth(width) => write!(f, "invalid bit width {width}"),
+            Error::Corrupt(what) => write!(f, "corrupt input: {what}"),
+            Error::OutOfRange(what) => write!(f, "value out of range: {what}"),
+            Error::ChecksumMismatch => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,180 @@
+// Self-describing on-disk block format.
+//
+// Codec payloads carry no metadata of their own: the reader must already know
+// the codec, the value count and (for some codecs) the bit width. This module
+// wraps a payload in a header that records all three and a checksum that
+// catches corruption.
+//
+// Layout, all integers little-endian:
+//
+//   offset  size  field
+//   0       4     magic "BPAK"
+//   4       1     format version, currently 1
+//   5       1     codec id, see `Codec`
+//   6       1     bit width: the widest width the payload packs at, or 0 if
+//                 the codec does not bitpack
+//   7       1     reserved, must be 0
+//   8       4     value count
+//   12      4     payload length n
+//   16      n     payload
+//   16 + n  4     CRC-32C of bytes [0, 16 + n)
+
+use crate::crc32c::crc32c;
+use crate::error::{Error, Result};
+
+pub const MAGIC: [u8; 4] = *b"BPAK";
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 16;
+const TRAILER_LEN: usize = 4;
+
+/// Codec that produced a block's payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// [`crate::block`]: u32 values, max-bits width per 128-value block.
+    Bitpack = 1,
+    /// [`crate::frame_of_ref::encode`]: u32 values.
+    For = 2,
+    /// [`crate::frame_of_ref::encode64`]: u64 values.
+    For64 = 3,
+    /// [`crate::pfor`]: u32 values with exceptions.
+    Pfor = 4,
+    /// [`crate::dod`]: i64 timestamps.
+    Dod = 5,
+    /// [`crate::gorilla`]: f64 values.
+    Gorilla = 6,
+    /// [`crate::chimp::encode`]: f64 values.
+    Chimp = 7,
+    /// [`crate::chimp::encode128`]: f64 values.
+    Chimp128 = 8,
+    /// [`crate::alp`]: f64 values.
+    Alp = 9,
+    /// [`crate::simple8b`]: u64 values below 2^60.
+    Simple8b = 10,
+    /// [`crate::leb128`]: u64 values.
+    Leb128 = 11,
+    /// [`crate::streamvbyte`]: u32 values.
+    StreamVByte = 12,
+    /// [`crate::parquet_rle::encode`]: u32 values.
+    ParquetRle = 13,
+    /// [`crate::dictionary`]: u64 values.
+    Dictionary = 14,
+    /// [`crate::delta::encode_series`]: u64 values.
+    Delta = 15,
+}
+
+impl Codec {
+    pub const ALL: [Codec; 15] = [
+        Codec::Bitpack,
+        Codec::For,
+        Codec::For64,
+        Codec::Pfor,
+        Codec::Dod,
+        Codec::Gorilla,
+        Codec::Chimp,
+        Codec::Chimp128,
+        Codec::Alp,
+        Codec::Simple8b,
+        Codec::Leb128,
+        Codec::StreamVByte,
+        Codec::ParquetRle,
+        Codec::Dictionary,
+        Codec::Delta,
+    ];
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Codec> {
+        Codec::ALL.into_iter().find(|codec| codec.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Bitpack => "bitpack",
+            Codec::For => "for",
+            Codec::For64 => "for64",
+            Codec::Pfor => "pfor",
+            Codec::Dod => "dod",
+            Codec::Gorilla => "gorilla",
+            Codec::Chimp => "chimp",
+            Codec::Chimp128 => "chimp128",
+            Codec::Alp => "alp",
+            Codec::Simple8b => "simple8b",
+            Codec::Leb128 => "leb128",
+            Codec::StreamVByte => "streamvbyte",
+            Codec::ParquetRle => "parquet-rle",
+            Codec::Dictionary => "dictionary",
+            Codec::Delta => "delta",
+        }
+    }
+}
+
+/// A block header together with the payload it describes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    pub codec: Codec,
+    pub bit_width: u8,
+    pub count: u32,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Block<'a> {
+    /// Appends the framed block to `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the payload is 4 GiB or larger.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let len = u32::try_from(self.payload.len()).expect("payload exceeds 4 GiB");
+        let start = out.len();
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&[VERSION, self.codec.id(), self.bit_width, 0]);
+        out.extend_from_slice(&self.count.to_le_bytes());
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(self.payload);
+        let crc = crc32c(&out[start..]);
+        out.extend_from_slice(&crc.to_le_bytes());
+    }
+
+    /// Parses and validates one block at the start of `input`.
+    ///
+    /// Returns the block and the number of bytes it occupies.
+    pub fn read(input: &'a [u8]) -> Result<(Block<'a>, usize)> {
+        let header = input.get(..HEADER_LEN).ok_or(Error::Truncated)?;
+        if header[..4] != MAGIC {
+            return Err(Error::Corrupt("bad magic"));
+        }
+        if header[4] != VERSION {
+            return Err(Error::Corrupt("unsupported format version"));
+        }
+        let codec = Codec::from_id(header[5]).ok_or(Error::Corrupt("unknown codec"))?;
+        let bit_width = header[6];
+        if bit_width > 64 {
+            return Err(Error::InvalidBitWidth(bit_width));
+        }
+        if header[7] != 0 {
+            return Err(Error::Corrupt("reserved byte is set"));
+        }
+        let count = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let len = u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize;
+
+        let end = HEADER_LEN
+            .checked_add(len)
+            .ok_or(Error::Corrupt("payload length overflows"))?;
+        let trailer = input.get(end..end + TRAILER_LEN).ok_or(Error::Truncated)?;
+        let expected = u32::from_le_bytes(trailer.try_into().unwrap());
+        if crc32c(&input[..end]) != expected {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        let block = Block {
+            codec,
+            bit_width,
+            count,
+            payload: &input[HEADER_LEN..end],
+        };
+        Ok((block, end + TRAILER_LEN))
+    }
+}
@@ -0,0 +1,114 @@
+// Frame-of-reference (FOR) encoding.
+//
+// Timestamps and counters are large but close together: 1643673600 needs 31
+// bits, while its distance from the smallest timestamp in the block may need
+// only a few. FOR stores each block's minimum once and bitpacks the residuals
+// `value - min`.
+//
+// Layout, repeated for every block of up to 128 values:
+//
+//   [min: u32 LE][bit width: u8][packed residuals]
+//
+// The 64-bit variant uses the same layout with an 8-byte minimum.
+
+use crate::bitpack::{pack64, packed_bytes, read_bytes, unpack64, write_bytes};
+use crate::block::{decode_block, encode_block, max_bits64, BLOCK_LEN};
+use crate::error::{Error, Result};
+
+/// Appends `values` to `out` as FOR blocks.
+pub fn encode(values: &[u32], out: &mut Vec<u8>) {
+    let mut residuals = [0u32; BLOCK_LEN];
+    for block in values.chunks(BLOCK_LEN) {
+        let min = block.iter().copied().min().unwrap_or(0);
+        for (r, &v) in residuals.iter_mut().zip(block) {
+            *r = v - min;
+        }
+        out.extend_from_slice(&min.to_le_bytes());
+        encode_block(&residuals[..block.len()], out);
+    }
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u32>) -> Result<usize> {
+    let mut values = [0u32; BLOCK_LEN];
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let len = remaining.min(BLOCK_LEN);
+        let min = input.get(pos..pos + 4).ok_or(Error::Truncated)?;
+        let min = u32::from_le_bytes(min.try_into().unwrap());
+        pos += 4;
+
+        pos += decode_block(&input[pos..], &mut values[..len])?;
+        // A corrupt header can push a residual past u32::MAX; wrap rather
+        // than panic, as nothing valid could have produced it.
+        out.extend(values[..len].iter().map(|&r| r.wrapping_add(min)));
+        remaining -= len;
+    }
+    Ok(pos)
+}
+
+/// 64-bit variant of [`encode`].
+pub fn encode64(values: &[u64], out: &mut Vec<u8>) {
+    for block in values.chunks(BLOCK_LEN) {
+        encode_block64(block, out);
+    }
+}
+
+/// 64-bit variant of [`decode`].
+pub fn decode64(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let mut values = [0u64; BLOCK_LEN];
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let len = remaining.min(BLOCK_LEN);
+        pos += decode_block64(&input[pos..], &mut values[..len])?;
+        out.extend_from_slice(&values[..len]);
+        remaining -= len;
+    }
+    Ok(pos)
+}
+
+// Size of the 64-bit block header: minimum and bit width.
+pub(crate) const HEADER_LEN64: usize = 9;
+
+// Appends one 64-bit block of at most `BLOCK_LEN` values.
+pub(crate) fn encode_block64(block: &[u64], out: &mut Vec<u8>) {
+    let min = block.iter().copied().min().unwrap_or(0);
+    let mut residuals = [0u64; BLOCK_LEN];
+    for (r, &v) in residuals.iter_mut().zip(block) {
+        *r = v - min;
+    }
+    let residuals = &residuals[..block.len()];
+    let width = max_bits64(residuals);
+
+    let mut words = [0u64; BLOCK_LEN];
+    pack64(residuals, width, &mut words);
+    out.extend_from_slice(&min.to_le_bytes());
+    out.push(width);
+    write_bytes(&words, packed_bytes(block.len(), width), out);
+}
+
+// Decodes one 64-bit block of `out.len()` values, returning the bytes consumed.
+pub(crate) fn decode_block64(input: &[u8], out: &mut [u64]) -> Result<usize> {
+    let header = input.get(..HEADER_LEN64).ok_or(Error::Truncated)?;
+    let min = u64::from_le_bytes(header[..8].try_into().unwrap());
+    let width = header[8];
+    if width > 64 {
+        return Err(Error::InvalidBitWidth(width));
+    }
+
+    let bytes = packed_bytes(out.len(), width);
+    let payload = input
+        .get(HEADER_LEN64..HEADER_LEN64 + bytes)
+        .ok_or(Error::Truncated)?;
+    let mut words = [0u64; BLOCK_LEN];
+    read_bytes(payload, &mut words);
+    unpack64(&words, width, out);
+    for v in out.iter_mut() {
+        *v = v.wrapping_add(min);
+    }
+    Ok(HEADER_LEN64 + bytes)
+}
@@ -0,0 +1,196 @@
+// Gorilla XOR compression for f64 values.
+//
+// Each value is XORed with its predecessor. Slowly changing floats share sign,
+// exponent and high mantissa bits, so the XOR has long runs of leading and
+// trailing zeros and only the "meaningful" bits in between are stored:
+//
+//   XOR == 0                          '0'
+//   bits fit the previous window      '10' + meaningful bits
+//   otherwise                         '11' + 5-bit leading zeros
+//                                          + 6-bit meaningful length
+//                                          + meaningful bits
+//
+// The first value is stored in full. Leading zeros are capped at 31 to fit
+// their field, and a meaningful length of 64 is written as 0. Everything works
+// on the raw IEEE 754 bits, so NaN payloads and -0.0 round-trip exactly.
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::{Error, Result};
+
+const LEADING_BITS: u32 = 5;
+const LENGTH_BITS: u32 = 6;
+const MAX_LEADING: u32 = (1 << LEADING_BITS) - 1;
+
+/// Streaming Gorilla XOR encoder.
+#[derive(Debug, Default)]
+pub struct GorillaEncoder {
+    writer: BitWriter,
+    prev: u64,
+    // (leading zeros, trailing zeros) of the last '11' record.
+    window: Option<(u32, u32)>,
+    count: usize,
+}
+
+impl GorillaEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one value to the stream.
+    pub fn push(&mut self, value: f64) {
+        let x = value.to_bits();
+        if self.count == 0 {
+            self.writer.write_bits(x, 64);
+        } else {
+            let xor = x ^ self.prev;
+            if xor == 0 {
+                self.writer.write_bit(false);
+            } else {
+                self.encode_xor(xor);
+            }
+        }
+        self.prev = x;
+        self.count += 1;
+    }
+
+    fn encode_xor(&mut self, xor: u64) {
+        self.writer.write_bit(true);
+        let leading = xor.leading_zeros().min(MAX_LEADING);
+        let trailing = xor.trailing_zeros();
+
+        match self.window {
+            // Control '10': the meaningful bits fall inside the previous window.
+            Some((prev_leading, prev_trailing))
+                if leading >= prev_leading && trailing >= prev_trailing =>
+            {
+                self.writer.write_bit(false);
+                let len = 64 - prev_leading - prev_trailing;
+                self.writer.write_bits(xor >> prev_trailing, len);
+            }
+            // Control '11': describe a new window.
+            _ => {
+                self.writer.write_bit(true);
+                let len = 64 - leading - trailing;
+                self.writer.write_bits(leading as u64, LEADING_BITS);
+                self.writer.write_bits(len as u64 & 63, LENGTH_BITS);
+                self.writer.write_bits(xor >> trailing, len);
+                self.window = Some((leading, trailing));
+            }
+        }
+    }
+
+    /// Number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the encoded stream, zero-padded to a whole byte.
+    pub fn finish(self) -> Vec<u8> {
+        self.writer.finish()
+    }
+}
+
+/// Decoder for a stream written by [`GorillaEncoder`].
+///
+/// The stream does not record its length, so the caller supplies the number
+/// of values. Iteration stops after that many values or at the first error.
+pub struct GorillaDecoder<'a> {
+    reader: BitReader<'a>,
+    remaining: usize,
+    first: bool,
+    prev: u64,
+    window: Option<(u32, u32)>,
+}
+
+impl<'a> GorillaDecoder<'a> {
+    pub fn new(input: &'a [u8], count: usize) -> Self {
+        GorillaDecoder {
+            reader: BitReader::new(input),
+            remaining: count,
+            first: true,
+            prev: 0,
+            window: None,
+        }
+    }
+
+    /// Input bytes consumed so far.
+    pub fn bytes_read(&self) -> usize {
+        self.reader.bytes_read()
+    }
+
+    fn next_bits(&mut self) -> Result<u64> {
+        if self.first {
+            return self.reader.read_bits(64);
+        }
+        if !self.reader.read_bit()? {
+            return Ok(self.prev);
+        }
+
+        let (leading, trailing) = if self.reader.read_bit()? {
+            let leading = self.reader.read_bits(LEADING_BITS)? as u32;
+            let len = match self.reader.read_bits(LENGTH_BITS)? as u32 {
+                0 => 64,
+                len => len,
+            };
+            if leading + len > 64 {
+                return Err(Error::Corrupt("meaningful bits exceed 64"));
+            }
+            let window = (leading, 64 - leading - len);
+            self.window = Some(window);
+            window
+        } else {
+            self.window
+                .ok_or(Error::Corrupt("window reused before being set"))?
+        };
+
+        let len = 64 - leading - trailing;
+        let xor = self.reader.read_bits(len)? << trailing;
+        Ok(self.prev ^ xor)
+    }
+}
+
+impl Iterator for GorillaDecoder<'_> {
+    type Item = Result<f64>;
+
+    fn next(&mut self) -> Option<Result<f64>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match self.next_bits() {
+            Ok(x) => {
+                self.prev = x;
+                self.first = false;
+                self.remaining -= 1;
+                Some(Ok(f64::from_bits(x)))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Appends the XOR stream for `values` to `out`.
+pub fn encode(values: &[f64], out: &mut Vec<u8>) {
+    let mut encoder = GorillaEncoder::new();
+    for &v in values {
+        encoder.push(v);
+    }
+    out.extend_from_slice(&encoder.finish());
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<f64>) -> Result<usize> {
+    let mut decoder = GorillaDecoder::new(input, count);
+    for v in decoder.by_ref() {
+        out.push(v?);
+    }
+    Ok(decoder.bytes_read())
+}
@@ -0,0 +1,276 @@
+// AVX2 kernels.
+//
+// Unpacking gathers, for each of four lanes, the 8 bytes starting at the byte
+// that holds the value's first bit. A value plus its sub-byte shift spans at
+// most 39 bits, so one per-lane `srlv` and a mask finish the job.
+//
+// Packing works per output word instead of per input value: lane `j` builds
+// 32-bit word `j` by gathering every value that overlaps it and shifting each
+// one into place. A value starting `d` bits into the word is shifted left by
+// `d`, or right by `-d` when it started in the previous word; AVX2 variable
+// shifts return 0 for counts of 32 or more, so both shifts can be applied
+// unconditionally and ORed together.
+//
+// Predicate evaluation extracts values the same way as unpacking, compares
+// eight at a time and keeps only the `movemask` bits.
+//
+// StreamVByte decoding shuffles two groups of four values at once, one per
+// 128-bit lane, since `vpshufb` never moves bytes across lanes.
+
+use std::arch::x86_64::*;
+
+use crate::block::{BLOCK_LEN, BLOCK_WORDS};
+use crate::delta::{self, Mode};
+use crate::predicate::Cmp;
+use crate::streamvbyte::{decode_scalar, LENGTHS, SHUFFLE};
+
+use super::block_words;
+
+// Room for one gather window past the last packed byte.
+const PADDED_WORDS: usize = BLOCK_WORDS + 1;
+
+/// # Safety
+///
+/// The CPU must support AVX2; `1 <= bit_width <= 32` and
+/// `out.len() == 2 * bit_width`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn pack_block(values: &[u32; BLOCK_LEN], bit_width: u8, out: &mut [u64]) {
+    let w = bit_width as i32;
+    let width = _mm256_set1_epi32(w);
+    let value_mask = _mm256_set1_epi32(((1u64 << w) - 1) as u32 as i32);
+    let block_len = _mm256_set1_epi32(BLOCK_LEN as i32);
+    let width_ps = _mm256_set1_ps(w as f32);
+    let ones = _mm256_set1_epi32(1);
+    // Values that can overlap one 32-bit word.
+    let overlaps = 32usize.div_ceil(bit_width as usize) + 1;
+
+    // Output as 32-bit words; an odd width leaves half of the last vector unused.
+    let mut words = [0u64; BLOCK_WORDS];
+    let dst = words.as_mut_ptr() as *mut __m256i;
+    let mut word_bits = _mm256_setr_epi32(0, 32, 64, 96, 128, 160, 192, 224);
+    let step = _mm256_set1_epi32(8 * 32);
+
+    for v in 0..(4 * w as usize).div_ceil(8) {
+        // First value overlapping each word. The quotient is exact in f32: its
+        // fractional part is a multiple of 1/w, far above the rounding error.
+        let mut index = _mm256_cvttps_epi32(_mm256_div_ps(_mm256_cvtepi32_ps(word_bits), width_ps));
+        let mut acc = _mm256_setzero_si256();
+        for _ in 0..overlaps {
+            let in_block = _mm256_cmpgt_epi32(block_len, index);
+            let value = _mm256_mask_i32gather_epi32::<4>(
+                _mm256_setzero_si256(),
+                values.as_ptr() as *const i32,
+                index,
+                in_block,
+            );
+            let value = _mm256_and_si256(value, value_mask);
+            let offset = _mm256_sub_epi32(_mm256_mullo_epi32(index, width), word_bits);
+            let left = _mm256_sllv_epi32(value, offset);
+            let right = _mm256_srlv_epi32(value, _mm256_sub_epi32(_mm256_setzero_si256(), offset));
+            acc = _mm256_or_si256(acc, _mm256_or_si256(left, right));
+            index = _mm256_add_epi32(index, ones);
+        }
+        _mm256_storeu_si256(dst.add(v), acc);
+        word_bits = _mm256_add_epi32(word_bits, step);
+    }
+    out.copy_from_slice(&words[..block_words(bit_width)]);
+}
+
+// Copies a packed block into a buffer with room for the last gather window.
+fn padded(packed: &[u64]) -> [u64; PADDED_WORDS] {
+    let mut padded = [0u64; PADDED_WORDS];
+    padded[..packed.len()].copy_from_slice(packed);
+    padded
+}
+
+// Loads the four values whose first bits are at `bits` into the 64-bit lanes
+// of a register, one value per lane.
+#[inline(always)]
+unsafe fn unpack_quad(base: *const i64, bits: __m128i, value_mask: __m256i) -> __m256i {
+    let bytes = _mm_srli_epi32::<3>(bits);
+    let shift = _mm256_cvtepu32_epi64(_mm_and_si128(bits, _mm_set1_epi32(7)));
+    let window = _mm256_i32gather_epi64::<1>(base, bytes);
+    _mm256_and_si256(_mm256_srlv_epi64(window, shift), value_mask)
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2; `1 <= bit_width <= 32` and
+/// `packed.len() == 2 * bit_width`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn unpack_block(packed: &[u64], bit_width: u8, out: &mut [u32; BLOCK_LEN]) {
+    let w = bit_width as i32;
+    let padded = padded(packed);
+    let base = padded.as_ptr() as *const i64;
+
+    let value_mask = _mm256_set1_epi64x(((1u64 << w) - 1) as i64);
+    let step = _mm_set1_epi32(4 * w);
+    // Gathered 64-bit lanes hold one value each in their low half.
+    let narrow = _mm256_setr_epi32(0, 2, 4, 6, 0, 2, 4, 6);
+    let mut bits = _mm_mullo_epi32(_mm_setr_epi32(0, 1, 2, 3), _mm_set1_epi32(w));
+
+    let dst = out.as_mut_ptr() as *mut __m128i;
+    for quad in 0..BLOCK_LEN / 4 {
+        let v = _mm256_permutevar8x32_epi32(unpack_quad(base, bits, value_mask), narrow);
+        _mm_storeu_si128(dst.add(quad), _mm256_castsi256_si128(v));
+        bits = _mm_add_epi32(bits, step);
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2; `1 <= bit_width <= 32`,
+/// `packed.len() == 2 * bit_width` and `cmp` is `Eq`, `Lt` or `Gt`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn select_block(
+    packed: &[u64],
+    bit_width: u8,
+    cmp: Cmp,
+    threshold: u32,
+) -> [u64; BLOCK_LEN / 64] {
+    let w = bit_width as i32;
+    let padded = padded(packed);
+    let base = padded.as_ptr() as *const i64;
+
+    let value_mask = _mm256_set1_epi64x(((1u64 << w) - 1) as i64);
+    let step = _mm_set1_epi32(4 * w);
+    // Two quads of 64-bit lanes -> eight 32-bit lanes in value order.
+    let narrow = _mm256_setr_epi32(0, 2, 4, 6, 0, 2, 4, 6);
+    // AVX2 has only signed compares; flipping the sign bit of both sides makes
+    // them order unsigned values correctly.
+    let sign = _mm256_set1_epi32(i32::MIN);
+    let t = _mm256_set1_epi32(threshold as i32);
+    let biased_t = _mm256_xor_si256(t, sign);
+    let mut bits = _mm_mullo_epi32(_mm_setr_epi32(0, 1, 2, 3), _mm_set1_epi32(w));
+
+    let mut mask = [0u64; BLOCK_LEN / 64];
+    for k in 0..BLOCK_LEN / 8 {
+        let lo = _mm256_permutevar8x32_epi32(unpack_quad(base, bits, value_mask), narrow);
+        bits = _mm_add_epi32(bits, step);
+        let hi = _mm256_permutevar8x32_epi32(unpack_quad(base, bits, value_mask), narrow);
+        bits = _mm_add_epi32(bits, step);
+        let v = _mm256_blend_epi32::<0b1111_0000>(lo, hi);
+
+        let hits = match cmp {
+            Cmp::Eq => _mm256_cmpeq_epi32(v, t),
+            Cmp::Lt => _mm256_cmpgt_epi32(biased_t, _mm256_xor_si256(v, sign)),
+            _ => _mm256_cmpgt_epi32(_mm256_xor_si256(v, sign), biased_t),
+        };
+        let hit_bits = _mm256_movemask_ps(_mm256_castsi256_ps(hits)) as u64;
+        mask[k / 8] |= hit_bits << (8 * (k % 8));
+    }
+    mask
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2; `1 <= bit_width <= 32` and
+/// `packed.len() == 2 * bit_width`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn aggregate_block(packed: &[u64], bit_width: u8) -> (u64, u32, u32) {
+    let w = bit_width as i32;
+    let padded = padded(packed);
+    let base = padded.as_ptr() as *const i64;
+
+    let value_mask = _mm256_set1_epi64x(((1u64 << w) - 1) as i64);
+    let step = _mm_set1_epi32(4 * w);
+    // Each quad lands twice in the narrowed register, which min and max ignore.
+    let narrow = _mm256_setr_epi32(0, 2, 4, 6, 0, 2, 4, 6);
+    let mut bits = _mm_mullo_epi32(_mm_setr_epi32(0, 1, 2, 3), _mm_set1_epi32(w));
+
+    // 32 quads of values below 2^32 cannot overflow the 64-bit sum lanes.
+    let mut sum = _mm256_setzero_si256();
+    let mut min = _mm256_set1_epi32(-1);
+    let mut max = _mm256_setzero_si256();
+    for _ in 0..BLOCK_LEN / 4 {
+        let v = unpack_quad(base, bits, value_mask);
+        sum = _mm256_add_epi64(sum, v);
+        let v = _mm256_permutevar8x32_epi32(v, narrow);
+        min = _mm256_min_epu32(min, v);
+        max = _mm256_max_epu32(max, v);
+        bits = _mm_add_epi32(bits, step);
+    }
+
+    let (mut lanes_sum, mut lanes_min, mut lanes_max) = ([0u64; 4], [0u32; 8], [0u32; 8]);
+    _mm256_storeu_si256(lanes_sum.as_mut_ptr() as *mut __m256i, sum);
+    _mm256_storeu_si256(lanes_min.as_mut_ptr() as *mut __m256i, min);
+    _mm256_storeu_si256(lanes_max.as_mut_ptr() as *mut __m256i, max);
+    let min = lanes_min.into_iter().min().unwrap();
+    let max = lanes_max.into_iter().max().unwrap();
+    (lanes_sum.into_iter().sum(), min, max)
+}
+
+// Undoes zigzag in all lanes: (r >> 1) ^ -(r & 1).
+#[inline(always)]
+unsafe fn unzigzag(r: __m256i) -> __m256i {
+    let sign = _mm256_sub_epi64(
+        _mm256_setzero_si256(),
+        _mm256_and_si256(r, _mm256_set1_epi64x(1)),
+    );
+    _mm256_xor_si256(_mm256_srli_epi64::<1>(r), sign)
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2; `out.len() == residuals.len()`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn prefix_sum(base: u64, residuals: &[u64], mode: Mode, out: &mut [u64]) {
+    let mut carry = _mm256_set1_epi64x(base as i64);
+    let quads = residuals.len() / 4;
+    let src = residuals.as_ptr() as *const __m256i;
+    let dst = out.as_mut_ptr() as *mut __m256i;
+    for i in 0..quads {
+        let mut x = _mm256_loadu_si256(src.add(i));
+        if mode == Mode::ZigZag {
+            x = unzigzag(x);
+        }
+        // Log-step scan: add the lanes one, then two positions to the left.
+        // [a, b, c, d] -> [a, a+b, b+c, c+d] -> [a, a+b, a+b+c, a+b+c+d]
+        let by1 = _mm256_blend_epi32::<0b0000_0011>(
+            _mm256_permute4x64_epi64::<0b10_01_00_00>(x),
+            _mm256_setzero_si256(),
+        );
+        x = _mm256_add_epi64(x, by1);
+        let by2 = _mm256_blend_epi32::<0b0000_1111>(
+            _mm256_permute4x64_epi64::<0b01_00_00_00>(x),
+            _mm256_setzero_si256(),
+        );
+        x = _mm256_add_epi64(x, by2);
+        x = _mm256_add_epi64(x, carry);
+        _mm256_storeu_si256(dst.add(i), x);
+        carry = _mm256_permute4x64_epi64::<0b11_11_11_11>(x);
+    }
+
+    let done = quads * 4;
+    let base = _mm256_extract_epi64::<3>(carry) as u64;
+    delta::prefix_sum(base, &residuals[done..], mode, &mut out[done..]);
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2; `control.len() == out.len().div_ceil(4)`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn streamvbyte_decode(control: &[u8], data: &[u8], out: &mut [u32]) -> usize {
+    let dst = out.as_mut_ptr() as *mut __m256i;
+    let (mut quad, mut pos) = (0, 0);
+    while quad + 2 <= out.len() / 4 {
+        let (lo, hi) = (control[quad] as usize, control[quad + 1] as usize);
+        // Both lanes load 16 bytes, however few they use.
+        let second = pos + LENGTHS[lo] as usize;
+        if second + 16 > data.len() {
+            break;
+        }
+        let bytes = _mm256_loadu2_m128i(
+            data.as_ptr().add(second) as *const __m128i,
+            data.as_ptr().add(pos) as *const __m128i,
+        );
+        let shuffle = _mm256_loadu2_m128i(
+            SHUFFLE[hi].as_ptr() as *const __m128i,
+            SHUFFLE[lo].as_ptr() as *const __m128i,
+        );
+        _mm256_storeu_si256(dst.add(quad / 2), _mm256_shuffle_epi8(bytes, shuffle));
+        pos = second + LENGTHS[hi] as usize;
+        quad += 2;
+    }
+    pos + decode_scalar(&control[quad..], &data[pos..], &mut out[4 * quad..])
+}
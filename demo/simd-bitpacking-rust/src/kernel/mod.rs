@@ -0,0 +1,299 @@
+// Hot loops with SSE4.1 and AVX2 variants, selected at runtime.
+//
+// Every kernel produces exactly the same output as its scalar counterpart; in
+// particular, blocks packed by one kernel can be unpacked by any other. The
+// scalar code is both the fallback for CPUs without SIMD support and the
+// reference the vectorized kernels are tested against.
+
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+#[cfg(target_arch = "x86_64")]
+mod sse41;
+
+use crate::aggregate::aggregate_scalar;
+use crate::bitpack::{pack, unpack};
+use crate::block::BLOCK_LEN;
+use crate::delta::{self, Mode};
+use crate::predicate::{select_scalar, Cmp};
+use crate::streamvbyte::decode_scalar;
+
+/// Instruction set used for the hot loops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// Portable shift/OR loop.
+    Scalar,
+    /// 128-bit kernel: values are merged pairwise in registers.
+    Sse41,
+    /// 256-bit kernel built on gathers and per-lane variable shifts.
+    Avx2,
+}
+
+impl Kernel {
+    /// The fastest kernel supported by the running CPU.
+    pub fn detect() -> Kernel {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Kernel::Avx2;
+            }
+            if is_x86_feature_detected!("sse4.1") {
+                return Kernel::Sse41;
+            }
+        }
+        Kernel::Scalar
+    }
+
+    /// Every kernel the running CPU supports, slowest first.
+    pub fn available() -> Vec<Kernel> {
+        [Kernel::Scalar, Kernel::Sse41, Kernel::Avx2]
+            .into_iter()
+            .filter(|kernel| kernel.is_available())
+            .collect()
+    }
+
+    /// Whether the running CPU supports this kernel.
+    pub fn is_available(self) -> bool {
+        match self {
+            Kernel::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => is_x86_feature_detected!("sse4.1"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            _ => false,
+        }
+    }
+
+    /// Short lowercase name for reports and benchmark ids, e.g. `"avx2"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kernel::Scalar => "scalar",
+            Kernel::Sse41 => "sse4.1",
+            Kernel::Avx2 => "avx2",
+        }
+    }
+
+    /// Packs a full block into the first `2 * bit_width` words of `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width > 32`, if `out` is too short, or if the kernel
+    /// is not supported by the running CPU.
+    pub fn pack_block(self, values: &[u32; BLOCK_LEN], bit_width: u8, out: &mut [u64]) {
+        assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+        let words = block_words(bit_width);
+        assert!(
+            out.len() >= words,
+            "output holds {} words, need {words}",
+            out.len()
+        );
+        assert!(
+            self.is_available(),
+            "{} kernel is not supported",
+            self.name()
+        );
+
+        let out = &mut out[..words];
+        match self {
+            // Width 0 writes nothing, so only the scalar loop needs to see it.
+            _ if bit_width == 0 => pack(values, bit_width, out),
+            // SAFETY: the CPU supports the kernel, checked above.
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => unsafe { sse41::pack_block(values, bit_width, out) },
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => unsafe { avx2::pack_block(values, bit_width, out) },
+            _ => pack(values, bit_width, out),
+        }
+    }
+
+    /// Unpacks a full block from the first `2 * bit_width` words of `packed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width > 32`, if `packed` is too short, or if the kernel
+    /// is not supported by the running CPU.
+    pub fn unpack_block(self, packed: &[u64], bit_width: u8, out: &mut [u32; BLOCK_LEN]) {
+        assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+        let words = block_words(bit_width);
+        assert!(
+            packed.len() >= words,
+            "input holds {} words, need {words}",
+            packed.len()
+        );
+        assert!(
+            self.is_available(),
+            "{} kernel is not supported",
+            self.name()
+        );
+
+        let packed = &packed[..words];
+        match self {
+            _ if bit_width == 0 => unpack(packed, bit_width, out),
+            // SAFETY: the CPU supports the kernel, checked above.
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => unsafe { sse41::unpack_block(packed, bit_width, out) },
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => unsafe { avx2::unpack_block(packed, bit_width, out) },
+            _ => unpack(packed, bit_width, out),
+        }
+    }
+
+    /// Evaluates `value <op> threshold` over a full block packed at
+    /// `bit_width` and returns its selection bitmask: bit `i % 64` of word
+    /// `i / 64` is set when value `i` matches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width > 32`, if `packed` is too short, or if the kernel
+    /// is not supported by the running CPU.
+    pub fn select_block(
+        self,
+        packed: &[u64],
+        bit_width: u8,
+        cmp: Cmp,
+        threshold: u32,
+    ) -> [u64; BLOCK_LEN / 64] {
+        assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+        let words = block_words(bit_width);
+        assert!(
+            packed.len() >= words,
+            "input holds {} words, need {words}",
+            packed.len()
+        );
+        assert!(
+            self.is_available(),
+            "{} kernel is not supported",
+            self.name()
+        );
+
+        let packed = &packed[..words];
+        let (base, negate) = cmp.base();
+        let mut mask = [0; BLOCK_LEN / 64];
+        match self {
+            _ if bit_width == 0 => select_scalar(packed, 0, BLOCK_LEN, base, threshold, &mut mask),
+            // SAFETY: the CPU supports the kernel, checked above.
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => {
+                mask = unsafe { sse41::select_block(packed, bit_width, base, threshold) }
+            }
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => {
+                mask = unsafe { avx2::select_block(packed, bit_width, base, threshold) }
+            }
+            _ => select_scalar(packed, bit_width, BLOCK_LEN, base, threshold, &mut mask),
+        }
+        if negate {
+            mask = mask.map(|word| !word);
+        }
+        mask
+    }
+
+    /// Sum, minimum and maximum of a full block packed at `bit_width`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width > 32`, if `packed` is too short, or if the kernel
+    /// is not supported by the running CPU.
+    pub fn aggregate_block(self, packed: &[u64], bit_width: u8) -> (u64, u32, u32) {
+        assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+        let words = block_words(bit_width);
+        assert!(
+            packed.len() >= words,
+            "input holds {} words, need {words}",
+            packed.len()
+        );
+        assert!(
+            self.is_available(),
+            "{} kernel is not supported",
+            self.name()
+        );
+
+        let packed = &packed[..words];
+        match self {
+            _ if bit_width == 0 => (0, 0, 0),
+            // SAFETY: the CPU supports the kernel, checked above.
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => unsafe { sse41::aggregate_block(packed, bit_width) },
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => unsafe { avx2::aggregate_block(packed, bit_width) },
+            _ => aggregate_scalar(packed, bit_width),
+        }
+    }
+
+    /// Rebuilds a series from delta residuals, starting at `base`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is shorter than `residuals` or if the kernel is not
+    /// supported by the running CPU.
+    pub fn prefix_sum(self, base: u64, residuals: &[u64], mode: Mode, out: &mut [u64]) {
+        assert!(
+            out.len() >= residuals.len(),
+            "output holds {} values, need {}",
+            out.len(),
+            residuals.len()
+        );
+        assert!(
+            self.is_available(),
+            "{} kernel is not supported",
+            self.name()
+        );
+
+        let out = &mut out[..residuals.len()];
+        match self {
+            // SAFETY: the CPU supports the kernel, checked above.
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => unsafe { sse41::prefix_sum(base, residuals, mode, out) },
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => unsafe { avx2::prefix_sum(base, residuals, mode, out) },
+            _ => delta::prefix_sum(base, residuals, mode, out),
+        }
+    }
+
+    /// Decodes `out.len()` values from StreamVByte control and data streams,
+    /// see [`crate::streamvbyte`], and returns the data bytes consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `control` has fewer than `out.len().div_ceil(4)` bytes, if
+    /// `data` is shorter than the control bytes describe, or if the kernel is
+    /// not supported by the running CPU.
+    pub fn streamvbyte_decode(self, control: &[u8], data: &[u8], out: &mut [u32]) -> usize {
+        let quads = out.len().div_ceil(4);
+        assert!(
+            control.len() >= quads,
+            "control holds {} bytes, need {quads}",
+            control.len()
+        );
+        assert!(
+            self.is_available(),
+            "{} kernel is not supported",
+            self.name()
+        );
+
+        let control = &control[..quads];
+        match self {
+            // SAFETY: the CPU supports the kernel, checked above.
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse41 => unsafe { sse41::streamvbyte_decode(control, data, out) },
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => unsafe { avx2::streamvbyte_decode(control, data, out) },
+            _ => decode_scalar(control, data, out),
+        }
+    }
+}
+
+/// Number of `u64` words in a full block packed at `bit_width`.
+pub(crate) fn block_words(bit_width: u8) -> usize {
+    BLOCK_LEN * bit_width as usize / 64
+}
+
+/// Packs a full block with the fastest kernel the CPU supports.
+pub fn pack_block(values: &[u32; BLOCK_LEN], bit_width: u8, out: &mut [u64]) {
+    Kernel::detect().pack_block(values, bit_width, out)
+}
+
+/// Unpacks a full block with the fastest kernel the CPU supports.
+pub fn unpack_block(packed: &[u64], bit_width: u8, out: &mut [u32; BLOCK_LEN]) {
+    Kernel::detect().unpack_block(packed, bit_width, out)
+}
@@ -0,0 +1,225 @@
+// SSE4.1 kernels.
+//
+// Without gathers or per-lane variable shifts, the 128-bit kernel leans on a
+// different observation: two adjacent values `a, b` packed at width `w` form
+// the 2w-bit chunk `a | b << w`, and that shift is the same for every lane.
+// Four values are merged into two chunks in one register, and only the chunk
+// stream (half as many items, or a quarter for w <= 16) is written with
+// scalar shifts.
+//
+// StreamVByte decoding needs only SSSE3's `pshufb`: one shuffle spreads the
+// data bytes of four values into four lanes.
+
+use std::arch::x86_64::*;
+
+use crate::block::BLOCK_LEN;
+use crate::delta::{self, Mode};
+use crate::predicate::Cmp;
+use crate::streamvbyte::{decode_scalar, LENGTHS, SHUFFLE};
+
+// Writes the low `width` bits of `chunk` at bit `pos` of a zeroed stream.
+#[inline(always)]
+fn put(out: &mut [u64], pos: usize, chunk: u64, width: usize) {
+    let (word, shift) = (pos / 64, pos % 64);
+    out[word] |= chunk << shift;
+    if shift + width > 64 {
+        out[word + 1] |= chunk >> (64 - shift);
+    }
+}
+
+// Reads `width <= 64` bits starting at bit `pos`.
+#[inline(always)]
+fn get(packed: &[u64], pos: usize, width: usize) -> u64 {
+    let (word, shift) = (pos / 64, pos % 64);
+    let mut v = packed[word] >> shift;
+    if shift + width > 64 {
+        v |= packed[word + 1] << (64 - shift);
+    }
+    if width < 64 {
+        v &= (1u64 << width) - 1;
+    }
+    v
+}
+
+/// # Safety
+///
+/// The CPU must support SSE4.1; `1 <= bit_width <= 32` and
+/// `out.len() == 2 * bit_width`.
+#[target_feature(enable = "sse4.1")]
+pub(super) unsafe fn pack_block(values: &[u32; BLOCK_LEN], bit_width: u8, out: &mut [u64]) {
+    let w = bit_width as usize;
+    let count = _mm_cvtsi32_si128(w as i32);
+    let value_mask = _mm_set1_epi32(((1u64 << w) - 1) as u32 as i32);
+    let low_half = _mm_set1_epi64x(0xffff_ffff);
+
+    out.fill(0);
+    let mut pos = 0;
+    for quad in values.chunks_exact(4) {
+        let v = _mm_and_si128(_mm_loadu_si128(quad.as_ptr() as *const __m128i), value_mask);
+        // [a, b, c, d] -> [a | b << w, c | d << w]
+        let even = _mm_and_si128(v, low_half);
+        let odd = _mm_srli_epi64::<32>(v);
+        let pairs = _mm_or_si128(even, _mm_sll_epi64(odd, count));
+
+        let lo = _mm_cvtsi128_si64(pairs) as u64;
+        let hi = _mm_extract_epi64::<1>(pairs) as u64;
+        if w <= 16 {
+            put(out, pos, lo | hi << (2 * w), 4 * w);
+        } else {
+            put(out, pos, lo, 2 * w);
+            put(out, pos + 2 * w, hi, 2 * w);
+        }
+        pos += 4 * w;
+    }
+}
+
+// Loads values `4k..4k + 4` into a register, where `pos == 4k * w`.
+#[inline(always)]
+unsafe fn unpack_quad(packed: &[u64], pos: usize, w: usize, value_mask: __m128i) -> __m128i {
+    let (lo, hi) = if w <= 16 {
+        let chunk = get(packed, pos, 4 * w);
+        (chunk & ((1u64 << (2 * w)) - 1), chunk >> (2 * w))
+    } else {
+        (get(packed, pos, 2 * w), get(packed, pos + 2 * w, 2 * w))
+    };
+
+    // [a | b << w, c | d << w] -> [a, b, c, d]
+    let pairs = _mm_set_epi64x(hi as i64, lo as i64);
+    let even = _mm_and_si128(pairs, value_mask);
+    let odd = _mm_and_si128(
+        _mm_srl_epi64(pairs, _mm_cvtsi32_si128(w as i32)),
+        value_mask,
+    );
+    _mm_or_si128(even, _mm_slli_epi64::<32>(odd))
+}
+
+/// # Safety
+///
+/// The CPU must support SSE4.1; `1 <= bit_width <= 32` and
+/// `packed.len() == 2 * bit_width`.
+#[target_feature(enable = "sse4.1")]
+pub(super) unsafe fn unpack_block(packed: &[u64], bit_width: u8, out: &mut [u32; BLOCK_LEN]) {
+    let w = bit_width as usize;
+    let value_mask = _mm_set1_epi64x(((1u64 << w) - 1) as i64);
+
+    for (k, quad) in out.chunks_exact_mut(4).enumerate() {
+        let v = unpack_quad(packed, k * 4 * w, w, value_mask);
+        _mm_storeu_si128(quad.as_mut_ptr() as *mut __m128i, v);
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support SSE4.1; `1 <= bit_width <= 32`,
+/// `packed.len() == 2 * bit_width` and `cmp` is `Eq`, `Lt` or `Gt`.
+#[target_feature(enable = "sse4.1")]
+pub(super) unsafe fn select_block(
+    packed: &[u64],
+    bit_width: u8,
+    cmp: Cmp,
+    threshold: u32,
+) -> [u64; BLOCK_LEN / 64] {
+    let w = bit_width as usize;
+    let value_mask = _mm_set1_epi64x(((1u64 << w) - 1) as i64);
+    // SSE has only signed compares; flipping the sign bit of both sides makes
+    // them order unsigned values correctly.
+    let sign = _mm_set1_epi32(i32::MIN);
+    let t = _mm_set1_epi32(threshold as i32);
+    let biased_t = _mm_xor_si128(t, sign);
+
+    let mut mask = [0u64; BLOCK_LEN / 64];
+    for k in 0..BLOCK_LEN / 4 {
+        let v = unpack_quad(packed, k * 4 * w, w, value_mask);
+        let hits = match cmp {
+            Cmp::Eq => _mm_cmpeq_epi32(v, t),
+            Cmp::Lt => _mm_cmplt_epi32(_mm_xor_si128(v, sign), biased_t),
+            _ => _mm_cmpgt_epi32(_mm_xor_si128(v, sign), biased_t),
+        };
+        let bits = _mm_movemask_ps(_mm_castsi128_ps(hits)) as u64;
+        mask[k / 16] |= bits << (4 * (k % 16));
+    }
+    mask
+}
+
+/// # Safety
+///
+/// The CPU must support SSE4.1; `1 <= bit_width <= 32` and
+/// `packed.len() == 2 * bit_width`.
+#[target_feature(enable = "sse4.1")]
+pub(super) unsafe fn aggregate_block(packed: &[u64], bit_width: u8) -> (u64, u32, u32) {
+    let w = bit_width as usize;
+    let value_mask = _mm_set1_epi64x(((1u64 << w) - 1) as i64);
+
+    // 32 quads of values below 2^32 cannot overflow the 64-bit sum lanes.
+    let mut sum = _mm_setzero_si128();
+    let mut min = _mm_set1_epi32(-1);
+    let mut max = _mm_setzero_si128();
+    for k in 0..BLOCK_LEN / 4 {
+        let v = unpack_quad(packed, k * 4 * w, w, value_mask);
+        sum = _mm_add_epi64(sum, _mm_cvtepu32_epi64(v));
+        sum = _mm_add_epi64(sum, _mm_cvtepu32_epi64(_mm_srli_si128::<8>(v)));
+        min = _mm_min_epu32(min, v);
+        max = _mm_max_epu32(max, v);
+    }
+
+    let sum = _mm_cvtsi128_si64(sum) as u64 + _mm_extract_epi64::<1>(sum) as u64;
+    let (mut lanes_min, mut lanes_max) = ([0u32; 4], [0u32; 4]);
+    _mm_storeu_si128(lanes_min.as_mut_ptr() as *mut __m128i, min);
+    _mm_storeu_si128(lanes_max.as_mut_ptr() as *mut __m128i, max);
+    let min = lanes_min.into_iter().min().unwrap();
+    let max = lanes_max.into_iter().max().unwrap();
+    (sum, min, max)
+}
+
+// Undoes zigzag in both lanes: (r >> 1) ^ -(r & 1).
+#[inline(always)]
+unsafe fn unzigzag(r: __m128i) -> __m128i {
+    let sign = _mm_sub_epi64(_mm_setzero_si128(), _mm_and_si128(r, _mm_set1_epi64x(1)));
+    _mm_xor_si128(_mm_srli_epi64::<1>(r), sign)
+}
+
+/// # Safety
+///
+/// The CPU must support SSE4.1; `out.len() == residuals.len()`.
+#[target_feature(enable = "sse4.1")]
+pub(super) unsafe fn prefix_sum(base: u64, residuals: &[u64], mode: Mode, out: &mut [u64]) {
+    let mut carry = _mm_set1_epi64x(base as i64);
+    let pairs = residuals.len() / 2;
+    let src = residuals.as_ptr() as *const __m128i;
+    let dst = out.as_mut_ptr() as *mut __m128i;
+    for i in 0..pairs {
+        let mut x = _mm_loadu_si128(src.add(i));
+        if mode == Mode::ZigZag {
+            x = unzigzag(x);
+        }
+        // [a, b] -> [a, a + b], then add the running total to both lanes.
+        x = _mm_add_epi64(x, _mm_slli_si128::<8>(x));
+        x = _mm_add_epi64(x, carry);
+        _mm_storeu_si128(dst.add(i), x);
+        carry = _mm_unpackhi_epi64(x, x);
+    }
+
+    let done = pairs * 2;
+    let base = _mm_cvtsi128_si64(carry) as u64;
+    delta::prefix_sum(base, &residuals[done..], mode, &mut out[done..]);
+}
+
+/// # Safety
+///
+/// The CPU must support SSE4.1; `control.len() == out.len().div_ceil(4)`.
+#[target_feature(enable = "sse4.1")]
+pub(super) unsafe fn streamvbyte_decode(control: &[u8], data: &[u8], out: &mut [u32]) -> usize {
+    let dst = out.as_mut_ptr() as *mut __m128i;
+    let (mut quad, mut pos) = (0, 0);
+    // Every shuffle loads 16 bytes, however few it uses; the scalar loop
+    // finishes the last ones.
+    while quad < out.len() / 4 && pos + 16 <= data.len() {
+        let c = control[quad] as usize;
+        let bytes = _mm_loadu_si128(data.as_ptr().add(pos) as *const __m128i);
+        let shuffle = _mm_loadu_si128(SHUFFLE[c].as_ptr() as *const __m128i);
+        _mm_storeu_si128(dst.add(quad), _mm_shuffle_epi8(bytes, shuffle));
+        pos += LENGTHS[c] as usize;
+        quad += 1;
+    }
+    pos + decode_scalar(&control[quad..], &data[pos..], &mut out[4 * quad..])
+}
@@ -0,0 +1,74 @@
+// LEB128 varints, the integer encoding of protobuf and DWARF.
+//
+// Seven value bits per byte, least significant group first; the high bit of a
+// byte is set when another byte follows. A u64 takes 1 to 10 bytes:
+//
+//   300 = 0b10_0101100  ->  [1_0101100, 0_0000010] = [0xac, 0x02]
+//
+// Signed values are zigzag-mapped first, like protobuf's sint64. The decoder
+// accepts redundant trailing zero groups, as protobuf does, but nothing that
+// would carry bits past 64.
+
+use crate::delta::{zigzag_decode, zigzag_encode};
+use crate::error::{Error, Result};
+
+/// Longest encoding of a u64.
+pub const MAX_LEN: usize = 10;
+
+/// Appends `value` as one varint.
+pub fn write_u64(value: u64, out: &mut Vec<u8>) {
+    let mut v = value;
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// Reads one varint from the start of `input`.
+///
+/// Returns the value and the number of bytes it occupies.
+pub fn read_u64(input: &[u8]) -> Result<(u64, usize)> {
+    let mut value = 0;
+    for (i, &byte) in input.iter().take(MAX_LEN).enumerate() {
+        // The tenth byte holds only bit 63.
+        if i == MAX_LEN - 1 && byte > 1 {
+            return Err(Error::Corrupt("varint exceeds 64 bits"));
+        }
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte < 0x80 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(Error::Truncated)
+}
+
+/// Appends `value` zigzag-mapped, so that small negative values stay short.
+pub fn write_i64(value: i64, out: &mut Vec<u8>) {
+    write_u64(zigzag_encode(value), out);
+}
+
+/// Reads one varint written by [`write_i64`].
+pub fn read_i64(input: &[u8]) -> Result<(i64, usize)> {
+    read_u64(input).map(|(v, len)| (zigzag_decode(v), len))
+}
+
+/// Appends `values` to `out`, one varint each.
+pub fn encode(values: &[u64], out: &mut Vec<u8>) {
+    for &v in values {
+        write_u64(v, out);
+    }
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let mut pos = 0;
+    for _ in 0..count {
+        let (value, len) = read_u64(&input[pos..])?;
+        out.push(value);
+        pos += len;
+    }
+    Ok(pos)
+}
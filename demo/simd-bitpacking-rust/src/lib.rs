@@ -0,0 +1,31 @@
+// Bit-packing building blocks for columnar TSDB experiments.
+//
+// Values are packed least-significant-bit first into a stream of u64 words,
+// so a value may start in one word and end in the next.
+
+pub mod adaptive;
+pub mod aggregate;
+pub mod alp;
+mod bitpack;
+pub mod bits;
+pub mod block;
+pub mod chimp;
+mod crc32c;
+pub mod delta;
+pub mod dictionary;
+pub mod dod;
+mod error;
+pub mod format;
+pub mod frame_of_ref;
+pub mod gorilla;
+pub mod kernel;
+pub mod leb128;
+pub mod parquet_rle;
+pub mod pfor;
+pub mod predicate;
+pub mod simple8b;
+pub mod stream;
+pub mod streamvbyte;
+
+pub use bitpack::{get, get64, pack, pack64, packed_len, unpack, unpack64};
+pub use error::{Error, Result};
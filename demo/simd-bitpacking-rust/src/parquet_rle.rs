@@ -0,0 +1,152 @@
+// Parquet's RLE / bit-packing hybrid encoding, as used for repetition and
+// definition levels and for dictionary indices.
+//
+// The data is a sequence of runs, each starting with a ULEB128 header whose
+// low bit gives its kind:
+//
+//   bit-packed run: header (groups << 1 | 1), then groups * bit_width bytes
+//                   holding 8 * groups values packed LSB first
+//   RLE run:        header (len << 1), then the repeated value in
+//                   ceil(bit_width / 8) little-endian bytes
+//
+// LSB-first packing of 8 values fills exactly `bit_width` bytes, so a group
+// is the same bits `pack` writes. The last bit-packed run may be padded with
+// zeros to a whole group; the reader knows the value count and drops the
+// padding. The encoder switches to an RLE run once a value repeats 8 times,
+// like parquet-mr, after first completing any partial bit-packed group with
+// the run's leading values.
+//
+// From the Parquet spec, 0..=7 at width 3 is one bit-packed group:
+//
+//   [0x03][0b10001000, 0b11000110, 0b11111010]
+//
+// Dictionary-encoded data pages prefix the runs with the bit width in one
+// byte; `encode` and `decode` use that layout.
+
+use crate::bitpack::mask;
+use crate::block::{max_bits, read_packed, write_packed, BLOCK_LEN};
+use crate::error::{Error, Result};
+use crate::leb128;
+
+// Repeats needed to leave a bit-packed run for an RLE run.
+const MIN_RUN: usize = 8;
+
+/// Appends `values` to `out` as RLE and bit-packed runs at `bit_width`.
+///
+/// Bits above `bit_width` are ignored.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 32`.
+pub fn encode_runs(values: &[u32], bit_width: u8, out: &mut Vec<u8>) {
+    assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+    let mask = mask(bit_width) as u32;
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let v = values[i] & mask;
+        let run = values[i..].iter().take_while(|&&x| x & mask == v).count();
+        i += run;
+
+        let fill = ((8 - literals.len() % 8) % 8).min(run);
+        literals.extend(std::iter::repeat_n(v, fill));
+        let rest = run - fill;
+        if rest >= MIN_RUN {
+            write_literals(&mut literals, bit_width, out);
+            leb128::write_u64((rest as u64) << 1, out);
+            let bytes = (bit_width as usize).div_ceil(8);
+            out.extend_from_slice(&v.to_le_bytes()[..bytes]);
+        } else {
+            literals.extend(std::iter::repeat_n(v, rest));
+        }
+    }
+    write_literals(&mut literals, bit_width, out);
+}
+
+// Writes and clears pending literals as one bit-packed run, zero-padded to a
+// whole group.
+fn write_literals(literals: &mut Vec<u32>, bit_width: u8, out: &mut Vec<u8>) {
+    if literals.is_empty() {
+        return;
+    }
+    literals.resize(literals.len().next_multiple_of(8), 0);
+    leb128::write_u64(((literals.len() / 8) as u64) << 1 | 1, out);
+    // Chunks hold whole groups, so each one starts on a byte boundary.
+    for chunk in literals.chunks(BLOCK_LEN) {
+        write_packed(chunk, bit_width, out);
+    }
+    literals.clear();
+}
+
+/// Decodes `count` values written by [`encode_runs`] at `bit_width` and
+/// appends them to `out`.
+///
+/// Returns the number of input bytes consumed, including the padding of the
+/// last bit-packed group.
+///
+/// An RLE run costs a few bytes whatever its length, so up to `count` values
+/// may come from a handful of input bytes. Callers decoding untrusted input
+/// must bound `count` themselves, as a framed block's length does.
+pub fn decode_runs(input: &[u8], bit_width: u8, count: usize, out: &mut Vec<u32>) -> Result<usize> {
+    if bit_width > 32 {
+        return Err(Error::InvalidBitWidth(bit_width));
+    }
+    let mut pos = 0;
+    let mut remaining = count;
+    let mut buf = [0u32; BLOCK_LEN];
+    while remaining > 0 {
+        let (header, len) = leb128::read_u64(&input[pos..])?;
+        pos += len;
+        if header & 1 == 1 {
+            let groups = header >> 1;
+            let bytes = groups.saturating_mul(bit_width as u64);
+            if bytes > (input.len() - pos) as u64 {
+                return Err(Error::Truncated);
+            }
+            let keep = groups.saturating_mul(8).min(remaining as u64) as usize;
+            let mut at = pos;
+            for first in (0..keep).step_by(BLOCK_LEN) {
+                let chunk = &mut buf[..(keep - first).min(BLOCK_LEN)];
+                at += read_packed(&input[at..], bit_width, chunk)?;
+                out.extend_from_slice(chunk);
+            }
+            pos += bytes as usize;
+            remaining -= keep;
+        } else {
+            let run = header >> 1;
+            if run > remaining as u64 {
+                return Err(Error::Corrupt("run exceeds value count"));
+            }
+            let bytes = (bit_width as usize).div_ceil(8);
+            let value = input.get(pos..pos + bytes).ok_or(Error::Truncated)?;
+            let mut le = [0; 4];
+            le[..bytes].copy_from_slice(value);
+            let value = u32::from_le_bytes(le);
+            if value as u64 > mask(bit_width) {
+                return Err(Error::Corrupt("run value exceeds bit width"));
+            }
+            out.extend(std::iter::repeat_n(value, run as usize));
+            pos += bytes;
+            remaining -= run as usize;
+        }
+    }
+    Ok(pos)
+}
+
+/// Appends `values` to `out` as a bit-width byte followed by runs at that
+/// width, the layout of Parquet's dictionary indices.
+pub fn encode(values: &[u32], out: &mut Vec<u8>) {
+    let bit_width = max_bits(values);
+    out.push(bit_width);
+    encode_runs(values, bit_width, out);
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed. As with [`decode_runs`], a
+/// short input can expand to `count` values, so bound `count` for untrusted
+/// input.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u32>) -> Result<usize> {
+    let (&bit_width, runs) = input.split_first().ok_or(Error::Truncated)?;
+    Ok(1 + decode_runs(runs, bit_width, count, out)?)
+}
@@ -0,0 +1,112 @@
+// Patched frame-of-reference (PFOR).
+//
+// Plain block packing lets one outlier drag all 128 values up to its width.
+// PFOR instead picks the narrowest width that still covers most of the block,
+// packs every value's low bits at that width, and stores the values that did
+// not fit as exceptions: their position and the bits above the width. The
+// decoder unpacks the low bits and patches the exceptions back in.
+//
+// Layout, repeated for every block of up to 128 values:
+//
+//   [bit width: u8][exception count: u8][packed low bits]
+//   [exception positions: 1 byte each][exception high bits: width-prefixed block]
+//
+// The high-bits block is omitted when there are no exceptions.
+
+use crate::block::{decode_block, encode_block, read_packed, write_packed, BLOCK_LEN};
+use crate::error::{Error, Result};
+
+/// Share of each block, in percent, that the chosen width must cover.
+pub const COVERAGE_PERCENT: usize = 90;
+
+fn bits(v: u32) -> u8 {
+    (32 - v.leading_zeros()) as u8
+}
+
+/// Narrowest width that holds at least [`COVERAGE_PERCENT`] of `block`.
+pub fn coverage_width(block: &[u32]) -> u8 {
+    let mut histogram = [0usize; 33];
+    for &v in block {
+        histogram[bits(v) as usize] += 1;
+    }
+
+    let needed = (block.len() * COVERAGE_PERCENT).div_ceil(100);
+    let mut covered = 0;
+    for (width, &count) in histogram.iter().enumerate() {
+        covered += count;
+        if covered >= needed {
+            return width as u8;
+        }
+    }
+    32
+}
+
+/// Appends `values` to `out` as PFOR blocks.
+///
+/// Returns the number of exceptions used by each block.
+pub fn encode(values: &[u32], out: &mut Vec<u8>) -> Vec<usize> {
+    let mut positions = [0u8; BLOCK_LEN];
+    let mut highs = [0u32; BLOCK_LEN];
+    let mut exceptions = Vec::with_capacity(values.len().div_ceil(BLOCK_LEN));
+    for block in values.chunks(BLOCK_LEN) {
+        let width = coverage_width(block);
+        let mut n = 0;
+        for (i, &v) in block.iter().enumerate() {
+            if bits(v) > width {
+                positions[n] = i as u8;
+                highs[n] = v >> width;
+                n += 1;
+            }
+        }
+
+        out.push(width);
+        out.push(n as u8);
+        // `pack` drops the bits above `width`; the exceptions keep them.
+        write_packed(block, width, out);
+        if n > 0 {
+            out.extend_from_slice(&positions[..n]);
+            encode_block(&highs[..n], out);
+        }
+        exceptions.push(n);
+    }
+    exceptions
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u32>) -> Result<usize> {
+    let mut values = [0u32; BLOCK_LEN];
+    let mut highs = [0u32; BLOCK_LEN];
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let len = remaining.min(BLOCK_LEN);
+        let header = input.get(pos..pos + 2).ok_or(Error::Truncated)?;
+        let (width, n) = (header[0], header[1] as usize);
+        pos += 2;
+        if n > len {
+            return Err(Error::Corrupt("exception count exceeds block"));
+        }
+        if n > 0 && width >= 32 {
+            return Err(Error::Corrupt("exceptions in a full-width block"));
+        }
+
+        pos += read_packed(&input[pos..], width, &mut values[..len])?;
+        if n > 0 {
+            let positions = input.get(pos..pos + n).ok_or(Error::Truncated)?;
+            pos += n;
+            pos += decode_block(&input[pos..], &mut highs[..n])?;
+            for (&p, &high) in positions.iter().zip(&highs) {
+                let slot = values[..len]
+                    .get_mut(p as usize)
+                    .ok_or(Error::Corrupt("exception position outside block"))?;
+                *slot |= high << width;
+            }
+        }
+
+        out.extend_from_slice(&values[..len]);
+        remaining -= len;
+    }
+    Ok(pos)
+}
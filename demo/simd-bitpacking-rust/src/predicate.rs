@@ -0,0 +1,197 @@
+// Predicate evaluation on packed data.
+//
+// Filters such as `value > threshold` are evaluated straight from the packed
+// words: the kernels extract values into registers, compare them there and
+// keep only the comparison mask, so no `u32` array is ever written. The result
+// is a selection bitmask in which bit `i % 64` of word `i / 64` is set when
+// value `i` matches.
+//
+// A block's bit width bounds its values to `0..2^width`, and for many
+// thresholds that alone decides the predicate for the whole block. Such blocks
+// are answered without reading their payload.
+
+use crate::bitpack::{get, mask, packed_bytes, read_bytes};
+use crate::block::{BLOCK_LEN, BLOCK_WORDS};
+use crate::error::{Error, Result};
+use crate::kernel::{block_words, Kernel};
+
+// Bitmask words per full block.
+const MASK_WORDS: usize = BLOCK_LEN / 64;
+
+/// Comparison of each value against a threshold, as in `value <op> threshold`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    /// Every comparison operator.
+    pub const ALL: [Cmp; 6] = [Cmp::Eq, Cmp::Ne, Cmp::Lt, Cmp::Le, Cmp::Gt, Cmp::Ge];
+
+    /// Evaluates `value <op> threshold` for a single value.
+    pub fn eval(self, value: u32, threshold: u32) -> bool {
+        match self {
+            Cmp::Eq => value == threshold,
+            Cmp::Ne => value != threshold,
+            Cmp::Lt => value < threshold,
+            Cmp::Le => value <= threshold,
+            Cmp::Gt => value > threshold,
+            Cmp::Ge => value >= threshold,
+        }
+    }
+
+    // Splits the operator into one of `Eq`, `Lt` or `Gt`, which the kernels
+    // implement, and whether to negate its result.
+    pub(crate) fn base(self) -> (Cmp, bool) {
+        match self {
+            Cmp::Eq => (Cmp::Eq, false),
+            Cmp::Ne => (Cmp::Eq, true),
+            Cmp::Lt => (Cmp::Lt, false),
+            Cmp::Ge => (Cmp::Lt, true),
+            Cmp::Gt => (Cmp::Gt, false),
+            Cmp::Le => (Cmp::Gt, true),
+        }
+    }
+}
+
+/// Evaluates `value <op> threshold` for the first `count` values of a stream
+/// packed by [`crate::pack`] and writes the selection bitmask to `out`.
+///
+/// Writes `ceil(count / 64)` words; bits past `count` in the last one are 0.
+///
+/// # Panics
+///
+/// Panics if `bit_width > 32`, if `packed` holds fewer than `count` values or
+/// if `out` is shorter than `ceil(count / 64)` words.
+pub fn select(
+    packed: &[u64],
+    bit_width: u8,
+    count: usize,
+    cmp: Cmp,
+    threshold: u32,
+    out: &mut [u64],
+) {
+    assert!(bit_width <= 32, "bit width {bit_width} exceeds 32");
+    assert!(
+        packed.len() >= crate::packed_len(count, bit_width),
+        "input holds {} words, need {}",
+        packed.len(),
+        crate::packed_len(count, bit_width)
+    );
+    let out = &mut out[..count.div_ceil(64)];
+
+    if let Some(all) = constant(cmp, threshold, bit_width) {
+        fill(all, count, out);
+        return;
+    }
+
+    let kernel = Kernel::detect();
+    let words = block_words(bit_width);
+    let full = count / BLOCK_LEN;
+    for (block, mask) in out.chunks_exact_mut(MASK_WORDS).take(full).enumerate() {
+        let packed = &packed[block * words..];
+        mask.copy_from_slice(&kernel.select_block(packed, bit_width, cmp, threshold));
+    }
+
+    let packed = &packed[full * words..];
+    let tail = &mut out[full * MASK_WORDS..];
+    select_scalar(
+        packed,
+        bit_width,
+        count - full * BLOCK_LEN,
+        cmp,
+        threshold,
+        tail,
+    );
+}
+
+/// Evaluates `value <op> threshold` over `count` values written by
+/// [`crate::block::encode`] and appends the selection bitmask to `out`.
+///
+/// Appends `ceil(count / 64)` words and returns the number of input bytes
+/// consumed.
+pub fn select_blocks(
+    input: &[u8],
+    count: usize,
+    cmp: Cmp,
+    threshold: u32,
+    out: &mut Vec<u64>,
+) -> Result<usize> {
+    let kernel = Kernel::detect();
+    let mut words = [0u64; BLOCK_WORDS];
+    let mut pos = 0;
+    for first in (0..count).step_by(BLOCK_LEN) {
+        let len = (count - first).min(BLOCK_LEN);
+        let &width = input.get(pos).ok_or(Error::Truncated)?;
+        if width > 32 {
+            return Err(Error::InvalidBitWidth(width));
+        }
+        let bytes = packed_bytes(len, width);
+        let payload = input
+            .get(pos + 1..pos + 1 + bytes)
+            .ok_or(Error::Truncated)?;
+        pos += 1 + bytes;
+
+        let start = out.len();
+        out.resize(start + len.div_ceil(64), 0);
+        let mask = &mut out[start..];
+        if let Some(all) = constant(cmp, threshold, width) {
+            fill(all, len, mask);
+            continue;
+        }
+        words.fill(0);
+        read_bytes(payload, &mut words);
+        if len == BLOCK_LEN {
+            mask.copy_from_slice(&kernel.select_block(&words, width, cmp, threshold));
+        } else {
+            select_scalar(&words, width, len, cmp, threshold, mask);
+        }
+    }
+    Ok(pos)
+}
+
+// The outcome shared by every value packed at `bit_width`, if the width alone
+// decides it.
+fn constant(cmp: Cmp, threshold: u32, bit_width: u8) -> Option<bool> {
+    let max = mask(bit_width) as u32;
+    let (base, negate) = cmp.base();
+    let all = match base {
+        _ if max == 0 => base.eval(0, threshold),
+        Cmp::Eq if threshold > max => false,
+        Cmp::Lt if threshold == 0 => false,
+        Cmp::Lt if threshold > max => true,
+        Cmp::Gt if threshold >= max => false,
+        _ => return None,
+    };
+    Some(all != negate)
+}
+
+// Sets the first `count` bits of `out` to `all` and clears the rest.
+fn fill(all: bool, count: usize, out: &mut [u64]) {
+    out.fill(if all { u64::MAX } else { 0 });
+    if all && !count.is_multiple_of(64) {
+        out[count / 64] = mask((count % 64) as u8);
+    }
+}
+
+// Scalar reference: one `get` and comparison per value.
+pub(crate) fn select_scalar(
+    packed: &[u64],
+    bit_width: u8,
+    count: usize,
+    cmp: Cmp,
+    threshold: u32,
+    out: &mut [u64],
+) {
+    out[..count.div_ceil(64)].fill(0);
+    for i in 0..count {
+        if cmp.eval(get(packed, bit_width, i), threshold) {
+            out[i / 64] |= 1 << (i % 64);
+        }
+    }
+}
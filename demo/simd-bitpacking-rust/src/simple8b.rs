@@ -0,0 +1,109 @@
+// Simple8b: word-aligned packing of small integers, as used by InfluxDB's TSM
+// engine for integers and timestamps.
+//
+// Every value group fills one u64 word. The top 4 bits select how the low 60
+// bits are divided:
+//
+//   selector   0    1    2   3   4   5   6   7   8   9  10  11  12  13  14  15
+//   values   240  120   60  30  20  15  12  10   8   7   6   5   4   3   2   1
+//   bits       0    0    1   2   3   4   5   6   7   8  10  12  15  20  30  60
+//
+// Selectors 0 and 1 store no payload: they are runs of 240 and 120 ones, the
+// common delta of a regular series. The first value of a word sits in its
+// least significant bits. The encoder takes the lowest selector whose whole
+// group fits, so a word never holds padding and the last words of a series
+// may use a wider selector than their values need. Values must be below 2^60.
+//
+// Words are stored little-endian, like the rest of the crate; InfluxDB writes
+// them big-endian.
+
+use crate::error::{Error, Result};
+
+/// Largest value Simple8b can store.
+pub const MAX_VALUE: u64 = (1 << 60) - 1;
+
+// (values per word, bits per value), indexed by selector.
+#[rustfmt::skip]
+const SELECTORS: [(usize, u32); 16] = [
+    (240, 0), (120, 0), (60, 1), (30, 2), (20, 3), (15, 4), (12, 5), (10, 6),
+    (8, 7), (7, 8), (6, 10), (5, 12), (4, 15), (3, 20), (2, 30), (1, 60),
+];
+
+// Lowest selector whose group fits at the start of `values`, which must not be
+// empty.
+fn selector(values: &[u64]) -> usize {
+    let ones = values.iter().take(240).take_while(|&&v| v == 1).count();
+    if ones == 240 {
+        return 0;
+    }
+    if ones >= 120 {
+        return 1;
+    }
+
+    // Width needed by the first n values, for every n up to 60.
+    let mut widths = [0u32; 61];
+    for (n, &v) in values.iter().take(60).enumerate() {
+        widths[n + 1] = widths[n].max(64 - v.leading_zeros());
+    }
+    (2..16)
+        .find(|&s| {
+            let (n, bits) = SELECTORS[s];
+            n <= values.len() && widths[n] <= bits
+        })
+        .unwrap_or(15)
+}
+
+// One word holding `values` under `selector`. Runs of ones have no payload.
+fn pack(selector: usize, values: &[u64]) -> u64 {
+    let mut word = (selector as u64) << 60;
+    let bits = SELECTORS[selector].1;
+    if bits > 0 {
+        for (i, &v) in values.iter().enumerate() {
+            word |= v << (i as u32 * bits);
+        }
+    }
+    word
+}
+
+/// Appends `values` to `out` as Simple8b words.
+///
+/// Fails without writing anything if a value exceeds [`MAX_VALUE`].
+pub fn encode(values: &[u64], out: &mut Vec<u8>) -> Result<()> {
+    if values.iter().any(|&v| v > MAX_VALUE) {
+        return Err(Error::OutOfRange("simple8b values must be below 2^60"));
+    }
+    let mut rest = values;
+    while !rest.is_empty() {
+        let s = selector(rest);
+        let n = SELECTORS[s].0;
+        out.extend_from_slice(&pack(s, &rest[..n]).to_le_bytes());
+        rest = &rest[n..];
+    }
+    Ok(())
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u64>) -> Result<usize> {
+    let mut pos = 0;
+    let mut remaining = count;
+    while remaining > 0 {
+        let bytes = input.get(pos..pos + 8).ok_or(Error::Truncated)?;
+        let word = u64::from_le_bytes(bytes.try_into().unwrap());
+        pos += 8;
+
+        let (n, bits) = SELECTORS[(word >> 60) as usize];
+        if n > remaining {
+            return Err(Error::Corrupt("word holds more values than remain"));
+        }
+        if bits == 0 {
+            out.extend(std::iter::repeat_n(1, n));
+        } else {
+            let mask = (1 << bits) - 1;
+            out.extend((0..n as u32).map(|i| word >> (i * bits) & mask));
+        }
+        remaining -= n;
+    }
+    Ok(pos)
+}
@@ -0,0 +1,178 @@
+// Streaming compression of u64 series in constant memory.
+//
+// The encoder buffers one block of values and writes it as a 64-bit FOR block
+// as soon as it fills up; the decoder reads one block at a time. A stream is a
+// sequence of length-prefixed blocks closed by an empty one:
+//
+//   [len: u8, 1..=128][min: u64 LE][bit width: u8][packed residuals]
+//   ...
+//   [0]
+//
+// Only the last block may be shorter than 128 values. A stream that ends
+// without the closing 0 was cut short and is reported as an error.
+
+use std::io::{self, Read, Write};
+
+use crate::bitpack::packed_bytes;
+use crate::block::BLOCK_LEN;
+use crate::error::Error;
+use crate::frame_of_ref::{decode_block64, encode_block64, HEADER_LEN64};
+
+/// Compresses values into a writer, one 128-value block at a time.
+///
+/// Call [`Encoder::finish`] once all values are pushed; dropping the encoder
+/// instead loses the buffered block and leaves the stream unterminated.
+pub struct Encoder<W: Write> {
+    writer: W,
+    buf: [u64; BLOCK_LEN],
+    len: usize,
+    scratch: Vec<u8>,
+}
+
+impl<W: Write> Encoder<W> {
+    /// Starts an empty stream that writes its blocks to `writer`.
+    pub fn new(writer: W) -> Self {
+        Encoder {
+            writer,
+            buf: [0; BLOCK_LEN],
+            len: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Appends one value, writing a block if it completes one.
+    pub fn push(&mut self, value: u64) -> io::Result<()> {
+        self.buf[self.len] = value;
+        self.len += 1;
+        if self.len == BLOCK_LEN {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    /// Appends a slice of values, writing every block it completes.
+    pub fn push_slice(&mut self, mut values: &[u64]) -> io::Result<()> {
+        while !values.is_empty() {
+            let take = (BLOCK_LEN - self.len).min(values.len());
+            self.buf[self.len..self.len + take].copy_from_slice(&values[..take]);
+            self.len += take;
+            values = &values[take..];
+            if self.len == BLOCK_LEN {
+                self.write_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        self.scratch.clear();
+        self.scratch.push(self.len as u8);
+        encode_block64(&self.buf[..self.len], &mut self.scratch);
+        self.len = 0;
+        self.writer.write_all(&self.scratch)
+    }
+
+    /// Writes the buffered values and the end marker, and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.len > 0 {
+            self.write_block()?;
+        }
+        self.writer.write_all(&[0])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Decompresses a stream written by [`Encoder`], one block at a time.
+///
+/// Iteration ends at the end marker or at the first error; [`Decoder::error`]
+/// tells the two apart.
+pub struct Decoder<R: Read> {
+    reader: R,
+    buf: [u64; BLOCK_LEN],
+    len: usize,
+    pos: usize,
+    done: bool,
+    error: Option<io::Error>,
+    scratch: Vec<u8>,
+}
+
+impl<R: Read> Decoder<R> {
+    /// Decodes the stream in `reader`, reading nothing until the first value
+    /// is requested.
+    pub fn new(reader: R) -> Self {
+        Decoder {
+            reader,
+            buf: [0; BLOCK_LEN],
+            len: 0,
+            pos: 0,
+            done: false,
+            error: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// The error that stopped iteration, if any.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    // Loads the next block into `buf`. Returns false at the end marker.
+    fn read_block(&mut self) -> io::Result<bool> {
+        let mut len = [0u8];
+        self.reader.read_exact(&mut len)?;
+        let len = len[0] as usize;
+        if len == 0 {
+            return Ok(false);
+        }
+        if len > BLOCK_LEN {
+            return Err(invalid(Error::Corrupt("block longer than 128 values")));
+        }
+
+        self.scratch.resize(HEADER_LEN64, 0);
+        self.reader.read_exact(&mut self.scratch)?;
+        let width = self.scratch[HEADER_LEN64 - 1];
+        if width > 64 {
+            return Err(invalid(Error::InvalidBitWidth(width)));
+        }
+        self.scratch
+            .resize(HEADER_LEN64 + packed_bytes(len, width), 0);
+        self.reader.read_exact(&mut self.scratch[HEADER_LEN64..])?;
+
+        decode_block64(&self.scratch, &mut self.buf[..len]).map_err(invalid)?;
+        self.len = len;
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+fn invalid(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.pos == self.len {
+            if self.done {
+                return None;
+            }
+            match self.read_block() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.done = true;
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+        let value = self.buf[self.pos];
+        self.pos += 1;
+        Some(value)
+    }
+}
@@ -0,0 +1,120 @@
+// StreamVByte: byte-aligned u32 varints with the lengths split out.
+//
+// Each value takes 1 to 4 bytes, and its length minus one is a 2-bit code.
+// Unlike LEB128, the codes are not interleaved with the data: four of them
+// share a control byte, first value in the low bits, and all control bytes
+// come before the data bytes. Decoding four values is then one table lookup
+// on the control byte for a shuffle mask and one `pshufb` over the next 16
+// data bytes; see `Kernel::streamvbyte_decode`.
+//
+//   [control: ceil(count / 4) bytes][data: little-endian value bytes]
+//
+// The codes of a last, partial control byte's unused slots are written as 0
+// and ignored when decoding.
+
+use crate::error::{Error, Result};
+use crate::kernel::Kernel;
+
+// Data bytes used by the four values under each control byte.
+pub(crate) const LENGTHS: [u8; 256] = lengths();
+
+// For each control byte, the `pshufb` mask that moves four values' data bytes
+// into four u32 lanes; 0x80 zeroes a byte.
+pub(crate) const SHUFFLE: [[u8; 16]; 256] = shuffles();
+
+const fn code(control: u8, slot: usize) -> usize {
+    (control >> (2 * slot)) as usize & 3
+}
+
+const fn lengths() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut c = 0;
+    while c < 256 {
+        let mut slot = 0;
+        while slot < 4 {
+            table[c] += code(c as u8, slot) as u8 + 1;
+            slot += 1;
+        }
+        c += 1;
+    }
+    table
+}
+
+const fn shuffles() -> [[u8; 16]; 256] {
+    let mut table = [[0x80; 16]; 256];
+    let mut c = 0;
+    while c < 256 {
+        let mut src = 0;
+        let mut slot = 0;
+        while slot < 4 {
+            let mut byte = 0;
+            while byte <= code(c as u8, slot) {
+                table[c][4 * slot + byte] = src;
+                src += 1;
+                byte += 1;
+            }
+            slot += 1;
+        }
+        c += 1;
+    }
+    table
+}
+
+fn byte_len(v: u32) -> usize {
+    (4 - v.leading_zeros() as usize / 8).max(1)
+}
+
+/// Appends `values` to `out` as a control stream followed by a data stream.
+pub fn encode(values: &[u32], out: &mut Vec<u8>) {
+    let start = out.len();
+    out.resize(start + values.len().div_ceil(4), 0);
+    for (i, &v) in values.iter().enumerate() {
+        let len = byte_len(v);
+        out[start + i / 4] |= ((len - 1) << (2 * (i % 4))) as u8;
+        out.extend_from_slice(&v.to_le_bytes()[..len]);
+    }
+}
+
+/// Decodes `count` values written by [`encode`] and appends them to `out`.
+///
+/// Returns the number of input bytes consumed.
+pub fn decode(input: &[u8], count: usize, out: &mut Vec<u32>) -> Result<usize> {
+    let control_len = count.div_ceil(4);
+    let control = input.get(..control_len).ok_or(Error::Truncated)?;
+    let data_len = data_len(control, count);
+    let data = &input[control_len..];
+    if data.len() < data_len {
+        return Err(Error::Truncated);
+    }
+
+    let start = out.len();
+    out.resize(start + count, 0);
+    Kernel::detect().streamvbyte_decode(control, data, &mut out[start..]);
+    Ok(control_len + data_len)
+}
+
+// Data bytes used by the first `count` values under `control`.
+pub(crate) fn data_len(control: &[u8], count: usize) -> usize {
+    let full: usize = control[..count / 4]
+        .iter()
+        .map(|&c| LENGTHS[c as usize] as usize)
+        .sum();
+    let partial = (0..count % 4)
+        .map(|slot| code(control[count / 4], slot) + 1)
+        .sum::<usize>();
+    full + partial
+}
+
+// Scalar reference: decodes `out.len()` values and returns the data bytes
+// consumed.
+pub(crate) fn decode_scalar(control: &[u8], data: &[u8], out: &mut [u32]) -> usize {
+    let mut pos = 0;
+    for (i, v) in out.iter_mut().enumerate() {
+        let len = code(control[i / 4], i % 4) + 1;
+        let mut bytes = [0; 4];
+        bytes[..len].copy_from_slice(&data[pos..pos + len]);
+        *v = u32::from_le_bytes(bytes);
+        pos += len;
+    }
+    pos
+}
@@ -0,0 +1,220 @@
+use simd_bitpacking_demo::adaptive::{self, Selector, FRAME_LEN};
+use simd_bitpacking_demo::format::{Block, Codec};
+use simd_bitpacking_demo::Error;
+
+// xorshift64, so the series are the same on every run.
+fn random(len: usize, seed: u64) -> Vec<u64> {
+    let mut x = seed;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        })
+        .collect()
+}
+
+fn timestamps(len: usize) -> Vec<u64> {
+    (0..len as u64)
+        .map(|i| 1_700_000_000 + i * 10 + (i % 3 == 0) as u64)
+        .collect()
+}
+
+fn status_codes(len: usize) -> Vec<u64> {
+    let codes = [200, 301, 404, 500, 503];
+    random(len, 7)
+        .iter()
+        .map(|r| codes[(r % 5) as usize])
+        .collect()
+}
+
+fn round_trip(selector: &Selector<impl adaptive::CostModel>, values: &[u64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    selector.encode(values, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(
+        adaptive::decode(&out, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    assert_eq!(decoded, values);
+    out
+}
+
+// The codec of every block in `bytes`.
+fn codecs(mut bytes: &[u8]) -> Vec<Codec> {
+    let mut codecs = Vec::new();
+    while !bytes.is_empty() {
+        let (block, len) = Block::read(bytes).unwrap();
+        codecs.push(block.codec);
+        bytes = &bytes[len..];
+    }
+    codecs
+}
+
+#[test]
+fn picks_the_codec_that_suits_the_series() {
+    let selector = Selector::new();
+    let small: Vec<u64> = random(FRAME_LEN, 1).iter().map(|r| r % 1000).collect();
+    let based: Vec<u64> = small.iter().map(|v| (1 << 40) + v).collect();
+    let runs: Vec<u64> = (0..FRAME_LEN as u64).map(|i| i / 300 % 3).collect();
+    // Mostly 4-bit values with a wide one every 50.
+    let spiky: Vec<u64> = random(FRAME_LEN, 2)
+        .iter()
+        .enumerate()
+        .map(|(i, r)| if i % 50 == 0 { r % (1 << 30) } else { r % 16 })
+        .collect();
+
+    assert_eq!(selector.choose(&timestamps(FRAME_LEN)), Codec::Delta);
+    assert_eq!(selector.choose(&status_codes(FRAME_LEN)), Codec::Dictionary);
+    assert_eq!(selector.choose(&runs), Codec::ParquetRle);
+    assert_eq!(selector.choose(&small), Codec::Bitpack);
+    assert_eq!(selector.choose(&based), Codec::For64);
+    assert_eq!(selector.choose(&spiky), Codec::Pfor);
+}
+
+#[test]
+fn codec_id_is_in_each_block_header() {
+    // A counter, then status codes, then a long run.
+    let mut values = timestamps(FRAME_LEN);
+    values.extend(status_codes(FRAME_LEN));
+    values.extend([42; 1000]);
+    let out = round_trip(&Selector::new(), &values);
+    assert_eq!(
+        codecs(&out),
+        [Codec::Delta, Codec::Dictionary, Codec::ParquetRle]
+    );
+
+    let (block, _) = Block::read(&out).unwrap();
+    assert_eq!(block.count, FRAME_LEN as u32);
+    // Steps of 10 and 11 next to the zero first residual take 4 bits.
+    assert_eq!(block.bit_width, 4);
+    assert!(out.len() < values.len());
+}
+
+#[test]
+fn block_header_widths() {
+    let width = |codec, values: &[u64]| {
+        let mut payload = Vec::new();
+        let block = adaptive::encode_block(codec, values, &mut payload);
+        block.map(|block| block.bit_width)
+    };
+
+    let ts = timestamps(300);
+    assert_eq!(width(Codec::Bitpack, &[5, 1, 0]), Some(3));
+    assert_eq!(width(Codec::ParquetRle, &[]), Some(0));
+    // Blocks of 128 timestamps span 1280 s.
+    assert_eq!(width(Codec::For64, &ts), Some(11));
+    assert_eq!(width(Codec::For, &ts), Some(11));
+    assert_eq!(width(Codec::Delta, &ts), Some(4));
+    assert_eq!(width(Codec::Dictionary, &status_codes(1000)), Some(3));
+    assert_eq!(width(Codec::Dictionary, &ts[..1]), Some(0));
+    assert_eq!(width(Codec::Leb128, &ts), Some(0));
+
+    // Pfor only holds u32s.
+    assert_eq!(width(Codec::Pfor, &[7, 1 << 32]), None);
+    let mut outlier = [7; 10];
+    outlier[9] = u64::from(u32::MAX);
+    assert_eq!(width(Codec::Pfor, &outlier), Some(3));
+    assert_eq!(width(Codec::Gorilla, &ts), None);
+}
+
+#[test]
+fn encoded_blocks_decode() {
+    let values = timestamps(1000);
+    let mut payload = vec![1, 2, 3];
+    let block = adaptive::encode_block(Codec::Delta, &values, &mut payload).unwrap();
+    assert_eq!(block.count, 1000);
+    let mut bytes = Vec::new();
+    block.write(&mut bytes);
+
+    let mut out = Vec::new();
+    assert_eq!(adaptive::decode(&bytes, 1000, &mut out), Ok(bytes.len()));
+    assert_eq!(out, values);
+}
+
+#[test]
+fn cost_model_trades_size_for_speed() {
+    let values = status_codes(FRAME_LEN);
+    // Charge the dictionary's lookups as if they cost a byte per value.
+    let selector = Selector::new().cost_model(|codec, bytes, values| match codec {
+        Codec::Dictionary => (bytes + values) as f64,
+        _ => bytes as f64,
+    });
+    assert_ne!(selector.choose(&values), Codec::Dictionary);
+    let out = round_trip(&selector, &values);
+    assert!(!codecs(&out).contains(&Codec::Dictionary));
+}
+
+#[test]
+fn restricted_candidates() {
+    // 31-bit timestamps take 5 LEB128 bytes but a whole Simple8b word.
+    let selector = Selector::new().candidates(&[Codec::Leb128, Codec::Simple8b]);
+    assert_eq!(selector.choose(&timestamps(FRAME_LEN)), Codec::Leb128);
+    round_trip(&selector, &timestamps(3 * FRAME_LEN + 5));
+
+    // 32-bit codecs step aside for values that do not fit them.
+    let wide = [u64::MAX, 0, 1 << 40];
+    let selector = Selector::new().candidates(&[Codec::Bitpack, Codec::StreamVByte]);
+    assert_eq!(selector.choose(&wide), Codec::For64);
+    let out = round_trip(&selector, &wide);
+    assert_eq!(codecs(&out), [Codec::For64]);
+}
+
+#[test]
+#[should_panic(expected = "gorilla is not an integer codec")]
+fn float_codecs_are_not_candidates() {
+    let _ = Selector::new().candidates(&[Codec::Bitpack, Codec::Gorilla]);
+}
+
+#[test]
+fn empty_input() {
+    assert!(round_trip(&Selector::new(), &[]).is_empty());
+    assert_eq!(adaptive::decode(&[], 0, &mut Vec::new()), Ok(0));
+}
+
+#[test]
+fn corrupt_input() {
+    let values = timestamps(300);
+    let mut out = Vec::new();
+    Selector::new().encode(&values, &mut out);
+
+    let mut decoded = Vec::new();
+    assert_eq!(
+        adaptive::decode(&out, 200, &mut decoded),
+        Err(Error::Corrupt("block holds more values than remain"))
+    );
+    assert_eq!(
+        adaptive::decode(&out, 301, &mut decoded),
+        Err(Error::Truncated)
+    );
+
+    // A valid block from a codec the selector never writes.
+    let mut floats = Vec::new();
+    let block = Block {
+        codec: Codec::Gorilla,
+        bit_width: 0,
+        count: 1,
+        payload: &[0; 8],
+    };
+    block.write(&mut floats);
+    assert_eq!(
+        adaptive::decode(&floats, 1, &mut decoded),
+        Err(Error::Corrupt("block codec is not an integer codec"))
+    );
+
+    // One RLE run of ones, far longer than the selector ever frames.
+    let count = 1 << 30;
+    let mut rle = Vec::new();
+    let block = Block {
+        codec: Codec::ParquetRle,
+        bit_width: 1,
+        count,
+        payload: &[1, 0x80, 0x80, 0x80, 0x80, 0x08, 1],
+    };
+    block.write(&mut rle);
+    assert_eq!(
+        adaptive::decode(&rle, count as usize, &mut decoded),
+        Err(Error::Corrupt("block exceeds frame length"))
+    );
+}
@@ -0,0 +1,142 @@
+use simd_bitpacking_demo::aggregate::{self, Aggregate};
+use simd_bitpacking_demo::block::{self, BLOCK_LEN};
+use simd_bitpacking_demo::kernel::Kernel;
+use simd_bitpacking_demo::{frame_of_ref, pack, unpack};
+
+// Deterministic values; every block has its own width and offset, and one
+// block is constant so that it encodes at width 0.
+fn values(len: usize) -> Vec<u64> {
+    let mut x = 0x9e37_79b9_7f4a_7c15u64;
+    (0..len)
+        .map(|i| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            match i / BLOCK_LEN {
+                1 => 42,
+                b => (b as u64 * 1_000_003) + (x >> (b * 9 % 64)),
+            }
+        })
+        .collect()
+}
+
+fn values32(len: usize) -> Vec<u32> {
+    values(len).into_iter().map(|v| v as u32).collect()
+}
+
+#[test]
+fn kernels_match_scalar_aggregate() {
+    for kernel in Kernel::available() {
+        for width in 0..=32u8 {
+            let values: Vec<u32> = values32(BLOCK_LEN);
+            let mut packed = [0u64; 64];
+            pack(&values, width, &mut packed);
+            let mut residuals = [0u32; BLOCK_LEN];
+            unpack(&packed, width, &mut residuals);
+
+            let sum = residuals.iter().map(|&v| v as u64).sum();
+            let min = *residuals.iter().min().unwrap();
+            let max = *residuals.iter().max().unwrap();
+            assert_eq!(
+                kernel.aggregate_block(&packed, width),
+                (sum, min, max),
+                "{} at width {width}",
+                kernel.name()
+            );
+        }
+    }
+}
+
+#[test]
+fn blocks_match_decoded_values() {
+    for len in [0, 1, 100, 5 * BLOCK_LEN, 7 * BLOCK_LEN + 3] {
+        let values = values32(len);
+        let mut bytes = Vec::new();
+        block::encode(&values, &mut bytes);
+
+        let mut decoded = Vec::new();
+        block::decode(&bytes, len, &mut decoded).unwrap();
+        let expected: Aggregate = decoded.iter().map(|&v| v as u64).collect();
+
+        let mut aggregate = Aggregate::default();
+        assert_eq!(
+            aggregate::blocks(&bytes, len, &mut aggregate),
+            Ok(bytes.len())
+        );
+        assert_eq!(aggregate, expected, "{len} values");
+    }
+}
+
+#[test]
+fn frame_of_ref_matches_decoded_values() {
+    for len in [0, 1, 100, 5 * BLOCK_LEN, 7 * BLOCK_LEN + 3] {
+        let values = values32(len);
+        let mut bytes = Vec::new();
+        frame_of_ref::encode(&values, &mut bytes);
+
+        let mut decoded = Vec::new();
+        frame_of_ref::decode(&bytes, len, &mut decoded).unwrap();
+        let expected: Aggregate = decoded.iter().map(|&v| v as u64).collect();
+
+        let mut aggregate = Aggregate::default();
+        assert_eq!(
+            aggregate::frame_of_ref(&bytes, len, &mut aggregate),
+            Ok(bytes.len())
+        );
+        assert_eq!(aggregate, expected, "{len} values");
+    }
+}
+
+#[test]
+fn frame_of_ref64_matches_decoded_values() {
+    for len in [0, 1, 100, 5 * BLOCK_LEN, 9 * BLOCK_LEN + 3] {
+        let mut values = values(len);
+        if let Some(last) = values.last_mut() {
+            *last = u64::MAX;
+        }
+        let mut bytes = Vec::new();
+        frame_of_ref::encode64(&values, &mut bytes);
+
+        let expected: Aggregate = values.iter().copied().collect();
+        let mut aggregate = Aggregate::default();
+        assert_eq!(
+            aggregate::frame_of_ref64(&bytes, len, &mut aggregate),
+            Ok(bytes.len())
+        );
+        assert_eq!(aggregate, expected, "{len} values");
+    }
+}
+
+#[test]
+fn wrapped_values_match_decoder() {
+    // A corrupt minimum pushes residuals past u32::MAX, which the decoder wraps.
+    let values: Vec<u32> = (0..BLOCK_LEN as u32).map(|i| i * 3).collect();
+    let mut bytes = Vec::new();
+    frame_of_ref::encode(&values, &mut bytes);
+    bytes[..4].copy_from_slice(&(u32::MAX - 10).to_le_bytes());
+
+    let mut decoded = Vec::new();
+    frame_of_ref::decode(&bytes, BLOCK_LEN, &mut decoded).unwrap();
+    let expected: Aggregate = decoded.iter().map(|&v| v as u64).collect();
+
+    let mut aggregate = Aggregate::default();
+    aggregate::frame_of_ref(&bytes, BLOCK_LEN, &mut aggregate).unwrap();
+    assert_eq!(aggregate, expected);
+}
+
+#[test]
+fn merge_empty() {
+    let mut aggregate = Aggregate::default();
+    aggregate.merge(Aggregate::default());
+    assert_eq!(aggregate.min, None);
+    aggregate.merge([3, 1, 2].into_iter().collect());
+    assert_eq!(
+        aggregate,
+        Aggregate {
+            count: 3,
+            sum: 6,
+            min: Some(1),
+            max: Some(3)
+        }
+    );
+}
@@ -0,0 +1,107 @@
+use simd_bitpacking_demo::alp::{self, VECTOR_LEN};
+use simd_bitpacking_demo::Error;
+
+fn bits(values: &[f64]) -> Vec<u64> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+fn round_trip(values: &[f64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    alp::encode(values, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(alp::decode(&out, values.len(), &mut decoded), Ok(out.len()));
+    assert_eq!(bits(&decoded), bits(values));
+    out
+}
+
+fn prices(len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|i| ((i * 7919) % 100_000) as f64 / 100.0 + 10.0)
+        .collect()
+}
+
+#[test]
+fn vector_boundaries() {
+    for len in [0, 1, 127, 128, 129, 1023, 1024, 1025, 3000] {
+        round_trip(&prices(len));
+    }
+}
+
+#[test]
+fn exponent_follows_decimal_places() {
+    let (e, f) = alp::choose_exponents(&[1.5, 2.25, 3.75, 0.05]);
+    assert_eq!(e - f, 2);
+    let (e, f) = alp::choose_exponents(&[12.0, 7.0, 3.0]);
+    assert_eq!(e - f, 0);
+}
+
+#[test]
+fn decimals_pack_tightly() {
+    // Two decimals between 10 and 1010: 17 bits per value, plus headers and
+    // the few values whose f64 products do not round-trip exactly.
+    let values = prices(10_000);
+    let out = round_trip(&values);
+    assert!(out.len() * 8 < values.len() * 20, "{} bytes", out.len());
+}
+
+#[test]
+fn special_values_are_exceptions() {
+    let mut values = prices(VECTOR_LEN);
+    let specials = [
+        f64::NAN,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        -0.0,
+        1e300,
+        f64::MIN_POSITIVE / 3.0,
+        std::f64::consts::PI,
+    ];
+    for (i, &special) in specials.iter().enumerate() {
+        values[i * 100 + 3] = special;
+    }
+    let with = round_trip(&values);
+    let without = round_trip(&prices(VECTOR_LEN));
+    // Each exception costs its position and value, and nothing else.
+    assert_eq!(with.len(), without.len() + specials.len() * 10);
+}
+
+#[test]
+fn only_exceptions() {
+    round_trip(&[f64::NAN; 300]);
+    round_trip(&[-0.0, f64::INFINITY, f64::from_bits(1)]);
+}
+
+#[test]
+fn corrupt_headers_are_rejected() {
+    let mut out = Vec::new();
+    alp::encode(&[1.5, f64::NAN], &mut out);
+    let decode = |bytes: &[u8]| alp::decode(bytes, 2, &mut Vec::new());
+
+    let mut bad = out.clone();
+    bad[0] = alp::MAX_EXPONENT + 1;
+    assert_eq!(decode(&bad), Err(Error::Corrupt("bad exponent or factor")));
+
+    let mut bad = out.clone();
+    bad[1] = bad[0] + 1;
+    assert_eq!(decode(&bad), Err(Error::Corrupt("bad exponent or factor")));
+
+    let mut bad = out.clone();
+    bad[2] = 3;
+    assert_eq!(
+        decode(&bad),
+        Err(Error::Corrupt("exception count exceeds vector"))
+    );
+
+    // The exception position sits just before its 8-byte value.
+    let mut bad = out.clone();
+    let at = bad.len() - 10;
+    bad[at] = 2;
+    assert_eq!(
+        decode(&bad),
+        Err(Error::Corrupt("exception position outside vector"))
+    );
+
+    for len in 0..out.len() {
+        assert_eq!(decode(&out[..len]), Err(Error::Truncated), "{len} bytes");
+    }
+}
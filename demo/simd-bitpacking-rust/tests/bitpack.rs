@@ -0,0 +1,142 @@
+use simd_bitpacking_demo::{pack, pack64, packed_len, unpack, unpack64};
+
+fn xorshift(seed: u64) -> impl FnMut() -> u64 {
+    let mut x = seed | 1;
+    move || {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x
+    }
+}
+
+// Lengths around word boundaries for every width.
+const LENGTHS: [usize; 7] = [0, 1, 2, 63, 64, 65, 129];
+
+#[test]
+fn every_width_u32() {
+    let mut next = xorshift(32);
+    for width in 0..=32u8 {
+        let mask = if width == 0 {
+            0
+        } else {
+            u32::MAX >> (32 - width)
+        };
+        for len in LENGTHS {
+            // Unmasked input: bits above the width must be dropped.
+            let values: Vec<u32> = (0..len).map(|_| next() as u32).collect();
+            let mut packed = vec![0; packed_len(len, width)];
+            pack(&values, width, &mut packed);
+
+            let mut out = vec![0; len];
+            unpack(&packed, width, &mut out);
+            let expected: Vec<u32> = values.iter().map(|v| v & mask).collect();
+            assert_eq!(out, expected, "width {width}, len {len}");
+        }
+    }
+}
+
+#[test]
+fn values_span_word_boundaries() {
+    for width in 1..=32u8 {
+        let mask = u32::MAX >> (32 - width);
+        // The first value whose bits cross from word 0 into word 1, if any.
+        let Some(index) = (0..64).find(|i| i * width as usize % 64 + width as usize > 64) else {
+            // 1, 2, 4, 8, 16 and 32 divide 64, so no value crosses.
+            assert_eq!(64 % width, 0);
+            continue;
+        };
+        let offset = index * width as usize % 64;
+        for len in 63..=65 {
+            let mut values = vec![0; len.max(index + 1)];
+            values[index] = mask;
+            let mut packed = vec![0; packed_len(values.len(), width)];
+            pack(&values, width, &mut packed);
+            assert_eq!(packed[0], (mask as u64) << offset, "width {width}");
+            assert_eq!(packed[1], (mask as u64) >> (64 - offset), "width {width}");
+
+            let mut out = vec![0; values.len()];
+            unpack(&packed, width, &mut out);
+            assert_eq!(out, values, "width {width}, len {len}");
+        }
+    }
+}
+
+#[test]
+fn every_width_u64() {
+    let mut next = xorshift(64);
+    for width in 0..=64u8 {
+        let mask = if width == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - width)
+        };
+        for len in LENGTHS {
+            let values: Vec<u64> = (0..len).map(|_| next()).collect();
+            let mut packed = vec![0; packed_len(len, width)];
+            pack64(&values, width, &mut packed);
+
+            let mut out = vec![0; len];
+            unpack64(&packed, width, &mut out);
+            let expected: Vec<u64> = values.iter().map(|v| v & mask).collect();
+            assert_eq!(out, expected, "width {width}, len {len}");
+        }
+    }
+}
+
+#[test]
+fn max_values_every_width() {
+    for width in 1..=64u8 {
+        let max = u64::MAX >> (64 - width);
+        let values = [max, 0, max, max, 1, max];
+        let mut packed = vec![0; packed_len(values.len(), width)];
+        pack64(&values, width, &mut packed);
+        let ones: u32 = packed.iter().map(|w| w.count_ones()).sum();
+        assert_eq!(ones, 4 * width as u32 + 1, "width {width}");
+
+        let mut out = [0; 6];
+        unpack64(&packed, width, &mut out);
+        assert_eq!(out, values, "width {width}");
+    }
+}
+
+#[test]
+fn u32_and_u64_layouts_agree() {
+    let values: Vec<u32> = (0..100u32).map(|i| i.wrapping_mul(2_654_435_761)).collect();
+    let wide: Vec<u64> = values.iter().map(|&v| v as u64).collect();
+    for width in 0..=32u8 {
+        let mut narrow_packed = vec![0; packed_len(100, width)];
+        let mut wide_packed = vec![0; packed_len(100, width)];
+        pack(&values, width, &mut narrow_packed);
+        pack64(&wide, width, &mut wide_packed);
+        assert_eq!(narrow_packed, wide_packed, "width {width}");
+    }
+}
+
+#[test]
+fn leaves_trailing_words_untouched() {
+    let mut packed = [u64::MAX; 4];
+    pack64(&[1, 2, 3], 64, &mut packed[..]);
+    assert_eq!(packed, [1, 2, 3, u64::MAX]);
+}
+
+#[test]
+fn get_every_width() {
+    use simd_bitpacking_demo::{get, get64};
+
+    let mut next = xorshift(7);
+    for width in 0..=64u8 {
+        let values: Vec<u64> = (0..130).map(|_| next()).collect();
+        let mut packed = vec![0; packed_len(values.len(), width)];
+        pack64(&values, width, &mut packed);
+
+        let mut expected = vec![0; values.len()];
+        unpack64(&packed, width, &mut expected);
+        for (i, &v) in expected.iter().enumerate() {
+            assert_eq!(get64(&packed, width, i), v, "width {width}, index {i}");
+            if width <= 32 {
+                assert_eq!(get(&packed, width, i), v as u32, "width {width}, index {i}");
+            }
+        }
+    }
+}
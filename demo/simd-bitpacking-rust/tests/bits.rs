@@ -0,0 +1,82 @@
+use simd_bitpacking_demo::bits::{BitReader, BitWriter};
+use simd_bitpacking_demo::Error;
+
+// Deterministic (value, width) fields covering every width from 0 to 64.
+fn fields() -> Vec<(u64, u32)> {
+    let mut x = 0x9e37_79b9_7f4a_7c15u64;
+    (0..2000)
+        .map(|i| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let n = (i * 7 % 65) as u32;
+            let value = if n == 64 { x } else { x & ((1 << n) - 1) };
+            (value, n)
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip_every_width() {
+    let fields = fields();
+    let mut writer = BitWriter::new();
+    for &(value, n) in &fields {
+        writer.write_bits(value, n);
+    }
+    let total: usize = fields.iter().map(|&(_, n)| n as usize).sum();
+    assert_eq!(writer.len_bits(), total);
+
+    let bytes = writer.finish();
+    assert_eq!(bytes.len(), total.div_ceil(8));
+
+    let mut reader = BitReader::new(&bytes);
+    for &(value, n) in &fields {
+        assert_eq!(reader.read_bits(n), Ok(value), "{n}-bit field");
+    }
+    assert_eq!(reader.remaining(), bytes.len() * 8 - total);
+}
+
+#[test]
+fn msb_first_layout() {
+    let mut writer = BitWriter::new();
+    writer.write_bit(true);
+    writer.write_bits(0b01, 2);
+    writer.write_bits(0xff_ffff, 4); // only the low 4 bits are written
+    writer.write_bits(0b1, 2);
+    assert_eq!(writer.finish(), [0b1011_1110, 0b1000_0000]);
+}
+
+#[test]
+fn flush_aligns_to_a_byte() {
+    let mut writer = BitWriter::new();
+    writer.write_bits(0b101, 3);
+    writer.flush();
+    assert_eq!(writer.len_bits(), 8);
+    writer.write_bits(0xab, 8);
+    assert_eq!(writer.finish(), [0b1010_0000, 0xab]);
+}
+
+#[test]
+fn peek_does_not_consume() {
+    let bytes = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89];
+    let mut reader = BitReader::new(&bytes);
+    assert_eq!(reader.peek(12), Ok(0xdea));
+    assert_eq!(reader.remaining(), 72);
+    assert_eq!(reader.read_bits(4), Ok(0xd));
+    assert_eq!(reader.peek(56), Ok(0x00ea_dbee_f012_3456));
+    assert_eq!(reader.read_bits(64), Ok(0xeadb_eef0_1234_5678));
+    assert_eq!(reader.remaining(), 4);
+    assert_eq!(reader.bytes_read(), 9);
+}
+
+#[test]
+fn truncation_consumes_nothing() {
+    let bytes = [0xff, 0x00, 0xaa];
+    let mut reader = BitReader::new(&bytes);
+    assert_eq!(reader.read_bits(20), Ok(0xff00a));
+    assert_eq!(reader.read_bits(5), Err(Error::Truncated));
+    assert_eq!(reader.read_bits(64), Err(Error::Truncated));
+    assert_eq!(reader.remaining(), 4);
+    assert_eq!(reader.read_bits(4), Ok(0xa));
+    assert_eq!(reader.read_bit(), Err(Error::Truncated));
+}
@@ -0,0 +1,113 @@
+use simd_bitpacking_demo::block::{self, BLOCK_LEN};
+use simd_bitpacking_demo::Error;
+
+// Widths differ from block to block, including an all-zero block.
+fn values() -> Vec<u32> {
+    (0..1000u32)
+        .map(|i| match i / BLOCK_LEN as u32 {
+            2 => 0,
+            b => i.wrapping_mul(2_654_435_761) >> (b * 4),
+        })
+        .collect()
+}
+
+fn round_trip(values: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    block::encode(values, &mut bytes);
+    let mut out = Vec::new();
+    assert_eq!(
+        block::decode(&bytes, values.len(), &mut out),
+        Ok(bytes.len())
+    );
+    assert_eq!(out, values);
+    bytes
+}
+
+#[test]
+fn lengths_around_a_block() {
+    let values = values();
+    for len in [0, 1, 127, 128, 129, 1000] {
+        round_trip(&values[..len]);
+    }
+    assert!(round_trip(&[]).is_empty());
+}
+
+#[test]
+fn header_is_the_block_max_bits() {
+    let values = values();
+    let bytes = round_trip(&values);
+    let mut pos = 0;
+    for chunk in values.chunks(BLOCK_LEN) {
+        let width = block::max_bits(chunk);
+        assert_eq!(bytes[pos], width);
+        pos += 1 + (chunk.len() * width as usize).div_ceil(8);
+    }
+    assert_eq!(pos, bytes.len());
+
+    // An all-zero block is its header alone.
+    assert_eq!(round_trip(&[0; BLOCK_LEN]), [0]);
+    // LSB first: 1, 0 and 5 at 3 bits each.
+    assert_eq!(round_trip(&[1, 0, 5]), [3, 0b0100_0001, 0b1]);
+    assert_eq!(round_trip(&[u32::MAX; 3])[0], 32);
+}
+
+#[test]
+fn corrupt_input() {
+    let mut bytes = Vec::new();
+    block::encode(&values()[..200], &mut bytes);
+    for len in [0, 1, bytes.len() - 1] {
+        assert_eq!(
+            block::decode(&bytes[..len], 200, &mut Vec::new()),
+            Err(Error::Truncated),
+            "{len} bytes"
+        );
+    }
+    assert_eq!(
+        block::decode(&[33, 0, 0, 0, 0, 0], 1, &mut Vec::new()),
+        Err(Error::InvalidBitWidth(33))
+    );
+}
+
+#[test]
+fn decode_range_matches_full_decode() {
+    let values = values();
+    let mut bytes = Vec::new();
+    block::encode(&values, &mut bytes);
+
+    for (start, len) in [
+        (0, 0),
+        (0, 1),
+        (5, 300),
+        (127, 2),
+        (256, 128),
+        (999, 1),
+        (0, 1000),
+    ] {
+        let mut out = Vec::new();
+        block::decode_range(&bytes, values.len(), start, len, &mut out).unwrap();
+        assert_eq!(out, values[start..start + len], "range {start}+{len}");
+    }
+}
+
+#[test]
+fn decode_range_stops_reading_after_the_range() {
+    let values = values();
+    let mut bytes = Vec::new();
+    block::encode(&values[..BLOCK_LEN], &mut bytes);
+    let first_block = bytes.len();
+    block::encode(&values[BLOCK_LEN..], &mut bytes);
+
+    let mut out = Vec::new();
+    block::decode_range(&bytes[..first_block], values.len(), 10, 100, &mut out).unwrap();
+    assert_eq!(out, values[10..110]);
+    assert_eq!(
+        block::decode_range(&bytes[..first_block], values.len(), 100, 100, &mut out),
+        Err(Error::Truncated)
+    );
+}
+
+#[test]
+#[should_panic(expected = "exceeds")]
+fn decode_range_past_the_end() {
+    block::decode_range(&[], 10, 5, 6, &mut Vec::new()).unwrap();
+}
@@ -0,0 +1,152 @@
+use simd_bitpacking_demo::chimp::{self, Chimp128Decoder, Chimp128Encoder, ChimpEncoder};
+use simd_bitpacking_demo::{gorilla, Error};
+
+type Codec = (
+    fn(&[f64], &mut Vec<u8>),
+    fn(&[u8], usize, &mut Vec<f64>) -> Result<usize, Error>,
+);
+
+const CODECS: [Codec; 2] = [
+    (chimp::encode, chimp::decode),
+    (chimp::encode128, chimp::decode128),
+];
+
+fn bits(values: &[f64]) -> Vec<u64> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+fn assert_round_trip(values: &[f64]) {
+    for (encode, decode) in CODECS {
+        let mut out = Vec::new();
+        encode(values, &mut out);
+        let mut decoded = Vec::new();
+        assert_eq!(decode(&out, values.len(), &mut decoded), Ok(out.len()));
+        assert_eq!(bits(&decoded), bits(values));
+    }
+}
+
+fn stream(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:08b}")).collect()
+}
+
+#[test]
+fn trailing_zeros_record_bits() {
+    // 12.0 ^ 12.5 = 0x0001000000000000: 15 leading zeros, rounded down to 12
+    // (code 2), and 48 trailing zeros, which leaves 4 meaningful bits.
+    let mut encoder = ChimpEncoder::new();
+    encoder.push(12.0);
+    encoder.push(12.5);
+    encoder.push(12.5);
+    let out = stream(&encoder.finish());
+
+    let expected = format!("{:064b}", 12.0f64.to_bits()) + "01" + "010" + "000100" + "0001" + "00";
+    assert_eq!(&out[..expected.len()], expected);
+    assert!(out[expected.len()..].bytes().all(|b| b == b'0'));
+}
+
+#[test]
+fn leading_zeros_records_bits() {
+    // XORs ending in a set bit are stored below their rounded leading zeros;
+    // the second one reuses them with '10'.
+    let a = 0x4000_0000_0000_0000u64;
+    let values = [a, a ^ 0x0000_ff00_0000_0001, a ^ 0x0000_8000_0000_0003].map(f64::from_bits);
+    let mut encoder = ChimpEncoder::new();
+    for v in values {
+        encoder.push(v);
+    }
+    let out = stream(&encoder.finish());
+
+    // Both XORs have 16 leading zeros (code 3), so 48 bits follow each.
+    let second = 0x0000_ff00_0000_0001u64 ^ 0x0000_8000_0000_0003;
+    let expected = format!("{a:064b}")
+        + "11"
+        + "011"
+        + &format!("{:048b}", 0x0000_ff00_0000_0001u64)
+        + "10"
+        + &format!("{second:048b}");
+    assert_eq!(&out[..expected.len()], expected);
+}
+
+#[test]
+fn ring_reference_bits() {
+    // A repeat is '00' for both; Chimp128 adds the slot it repeats (0).
+    let mut plain = ChimpEncoder::new();
+    let mut ring = Chimp128Encoder::new();
+    for _ in 0..2 {
+        plain.push(1.1);
+        ring.push(1.1);
+    }
+    let first = format!("{:064b}", 1.1f64.to_bits());
+    assert_eq!(stream(&plain.finish()), first.clone() + "00" + "000000");
+    assert_eq!(stream(&ring.finish()), first + "00" + "0000000" + "0000000");
+
+    assert_round_trip(&[1.1, 2.7, 1.1, 2.7, 5.0, 1.1]);
+}
+
+#[test]
+fn special_values_are_bit_exact() {
+    let values = [
+        0.0,
+        -0.0,
+        f64::from_bits(0x7ff8_0000_0000_0001), // quiet NaN with payload
+        f64::from_bits(0x7ff0_0000_0000_0001), // signalling NaN
+        f64::from_bits(0xfff8_dead_beef_0000), // negative NaN
+        f64::NAN,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::MIN_POSITIVE / 3.0, // subnormal
+        f64::MAX,
+        -0.0,
+        1.0,
+        -1.0,
+        f64::from_bits(0x8000_0000_0000_0001), // full-width XOR with 1.0
+    ];
+    assert_round_trip(&values);
+}
+
+#[test]
+fn ring_wraps_around() {
+    // 300 distinct values, then the last 200 again: only the most recent 128
+    // are still in the ring.
+    let mut values: Vec<f64> = (0..300).map(|i| (i as f64 * 1.37).sin() * 1e3).collect();
+    values.extend_from_within(100..300);
+    assert_round_trip(&values);
+}
+
+#[test]
+fn chimp128_beats_gorilla_on_repeating_values() {
+    let values: Vec<f64> = (0..2000)
+        .map(|i| [21.5, 22.25, 19.75, 23.1][i * 7 % 4] + (i % 5) as f64)
+        .collect();
+    let mut gorilla_out = Vec::new();
+    gorilla::encode(&values, &mut gorilla_out);
+    let mut chimp_out = Vec::new();
+    chimp::encode128(&values, &mut chimp_out);
+    assert!(
+        chimp_out.len() * 2 < gorilla_out.len(),
+        "{} vs {}",
+        chimp_out.len(),
+        gorilla_out.len()
+    );
+}
+
+#[test]
+fn empty_ring_slot_is_corrupt() {
+    // A '00' record pointing at slot 5 when only one value was decoded.
+    let mut bytes = 1.0f64.to_bits().to_be_bytes().to_vec();
+    bytes.extend_from_slice(&[0b0000_0101, 0]);
+    let decoded: Result<Vec<f64>, Error> = Chimp128Decoder::new(&bytes, 2).collect();
+    assert_eq!(
+        decoded,
+        Err(Error::Corrupt("reference to an empty ring slot"))
+    );
+}
+
+#[test]
+fn truncated_stream() {
+    for (encode, decode) in CODECS {
+        let mut out = Vec::new();
+        encode(&[1.0, 2.0, 3.0], &mut out);
+        assert_eq!(decode(&out[..9], 3, &mut Vec::new()), Err(Error::Truncated));
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use simd_bitpacking_demo::format::{Block, Codec};
+
+fn bitpack(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bitpack"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// A scratch directory per test, so tests can run in parallel.
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bitpack-cli-{}-{test}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Second timestamps, a counter and a gauge.
+fn write_csv(dir: &Path) -> String {
+    let mut csv = String::from("ts,requests,cpu\n");
+    for i in 0..1000u64 {
+        let ts = 1_700_000_000 + i * 10 + (i % 3 == 0) as u64;
+        csv += &format!("{ts},{},{}\n", i * 7 % 500, 0.25 * (i % 40) as f64);
+    }
+    let path = dir.join("input.csv");
+    fs::write(&path, csv).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn compress_and_decompress_every_column() {
+    let dir = scratch("roundtrip");
+    let input = write_csv(&dir);
+    let csv = fs::read_to_string(&input).unwrap();
+
+    for (index, column, codec) in [
+        (0, "ts", "dod"),
+        (1, "requests", "pfor"),
+        (2, "cpu", "gorilla"),
+        (0, "ts", "auto"),
+    ] {
+        let packed = dir.join(format!("{column}.bpak"));
+        let packed = packed.to_str().unwrap();
+        let out = bitpack(&[
+            "compress", "--column", column, "--codec", codec, "-o", packed, &input,
+        ]);
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        assert!(fs::metadata(packed).unwrap().len() < 1000 * 8 / 2);
+
+        let out = bitpack(&["decompress", packed]);
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        let expected: Vec<&str> = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(index).unwrap())
+            .collect();
+        let restored = String::from_utf8(out.stdout).unwrap();
+        let restored: Vec<&str> = restored.lines().collect();
+        assert_eq!(restored, expected, "{column} with {codec}");
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stats_lists_every_codec() {
+    let dir = scratch("stats");
+    let input = write_csv(&dir);
+    let out = bitpack(&["stats", "--column", "requests", &input]);
+    assert!(out.status.success());
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(report.starts_with("requests: 1000 values, 8000 bytes raw\n"));
+    for codec in [
+        "bitpack",
+        "for",
+        "for64",
+        "pfor",
+        "dod",
+        "gorilla",
+        "chimp",
+        "chimp128",
+        "alp",
+        "simple8b",
+        "leb128",
+        "streamvbyte",
+        "parquet-rle",
+        "dictionary",
+        "delta",
+        "auto",
+    ] {
+        assert!(
+            report.contains(&format!("\n  {codec} ")),
+            "{codec} missing:\n{report}"
+        );
+    }
+
+    // Floats only fit gorilla; the others say why they were skipped.
+    let out = bitpack(&["stats", "--column", "cpu", &input]);
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(report.contains("(dod: needs integer values)"), "{report}");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn errors_are_reported() {
+    let dir = scratch("errors");
+    let input = write_csv(&dir);
+
+    let out = bitpack(&["compress", "--column", "cpu", "--codec", "for", &input]);
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stderr),
+        "bitpack: for: needs integer values\n"
+    );
+
+    let out = bitpack(&["compress", "--column", "missing", "--codec", "dod", &input]);
+    assert!(!out.status.success());
+
+    let out = bitpack(&["compress", "--bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+
+    let out = bitpack(&["decompress", &input]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("bad block"));
+
+    // Blank lines still count towards the line number.
+    let signed = dir.join("signed.csv");
+    fs::write(&signed, "n\n\n1\n\n-2\n").unwrap();
+    let signed = signed.to_str().unwrap();
+    let out = bitpack(&["compress", "--column", "n", "--codec", "delta", signed]);
+    assert_eq!(
+        String::from_utf8_lossy(&out.stderr),
+        "bitpack: delta: negative values\n"
+    );
+    fs::write(signed, "n\n\n1\n\n-2\nx\n").unwrap();
+    let out = bitpack(&["compress", "--column", "n", "--codec", "delta", signed]);
+    assert_eq!(
+        String::from_utf8_lossy(&out.stderr),
+        "bitpack: column n, line 6: not a number: \"x\"\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stats_on_an_empty_column() {
+    let dir = scratch("empty");
+    let input = dir.join("empty.csv");
+    fs::write(&input, "n\n").unwrap();
+    let out = bitpack(&["stats", "--column", "n", input.to_str().unwrap()]);
+    assert!(out.status.success());
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(report.starts_with("n: 0 values, 0 bytes raw\n"));
+    assert!(!report.contains("NaN"), "{report}");
+    assert!(
+        report.contains("\n  delta                  0        -       0.00\n"),
+        "{report}"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn oversized_frames_are_rejected() {
+    let dir = scratch("oversized");
+    // One RLE run of 0xF0000000 ones at width 1: 7 payload bytes that would
+    // decode to 15 GiB of u32s.
+    let count = 0xF000_0000u32;
+    let mut payload = vec![1];
+    let mut header = u64::from(count) << 1;
+    while header >= 0x80 {
+        payload.push(header as u8 | 0x80);
+        header >>= 7;
+    }
+    payload.extend([header as u8, 1]);
+    let mut bytes = Vec::new();
+    let block = Block {
+        codec: Codec::ParquetRle,
+        bit_width: 1,
+        count,
+        payload: &payload,
+    };
+    block.write(&mut bytes);
+    assert_eq!(bytes.len(), 27);
+    let packed = dir.join("oversized.bpak");
+    fs::write(&packed, bytes).unwrap();
+
+    let out = bitpack(&["decompress", packed.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stderr),
+        "bitpack: bad block: corrupt input: block exceeds frame length\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
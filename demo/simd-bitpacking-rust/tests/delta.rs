@@ -0,0 +1,107 @@
+use simd_bitpacking_demo::delta::{self, zigzag_decode, zigzag_encode, Mode};
+use simd_bitpacking_demo::kernel::Kernel;
+
+fn round_trip_u64(base: u64, values: &[u64]) -> (Mode, Vec<u64>) {
+    let mut residuals = vec![0; values.len()];
+    let mode = delta::encode_u64(base, values, &mut residuals);
+    let mut out = vec![0; values.len()];
+    delta::decode_u64(base, &residuals, mode, &mut out);
+    assert_eq!(out, values);
+    (mode, residuals)
+}
+
+fn round_trip_i64(base: i64, values: &[i64]) -> (Mode, Vec<u64>) {
+    let mut residuals = vec![0; values.len()];
+    let mode = delta::encode_i64(base, values, &mut residuals);
+    let mut out = vec![0; values.len()];
+    delta::decode_i64(base, &residuals, mode, &mut out);
+    assert_eq!(out, values);
+    (mode, residuals)
+}
+
+#[test]
+fn zigzag_orders_by_magnitude() {
+    let signed = [0, -1, 1, -2, 2, -3, i64::MAX, i64::MIN];
+    let mapped = signed.map(zigzag_encode);
+    assert_eq!(mapped, [0, 1, 2, 3, 4, 5, u64::MAX - 1, u64::MAX]);
+    assert_eq!(mapped.map(zigzag_decode), signed);
+}
+
+#[test]
+fn sorted_series_keep_raw_deltas() {
+    let (mode, residuals) = round_trip_u64(100, &[100, 103, 103, 110]);
+    assert_eq!(mode, Mode::Sorted);
+    assert_eq!(residuals, [0, 3, 0, 7]);
+
+    // Negative but non-decreasing i64 values are sorted too.
+    let (mode, residuals) = round_trip_i64(-10, &[-7, -7, 0, 5]);
+    assert_eq!(mode, Mode::Sorted);
+    assert_eq!(residuals, [3, 0, 7, 5]);
+
+    assert_eq!(round_trip_u64(0, &[]).0, Mode::Sorted);
+}
+
+#[test]
+fn unsorted_series_are_zigzagged() {
+    // One step back is enough to leave sorted mode.
+    let (mode, residuals) = round_trip_u64(0, &[5, 4, 6]);
+    assert_eq!(mode, Mode::ZigZag);
+    assert_eq!(residuals, [10, 1, 4]);
+
+    // So is a first value below the base.
+    assert_eq!(round_trip_u64(10, &[9, 20]).0, Mode::ZigZag);
+
+    // Small steps either way stay small, whatever the sign of the values.
+    let (mode, residuals) = round_trip_i64(-1000, &[-1001, -999, -1002, -1000]);
+    assert_eq!(mode, Mode::ZigZag);
+    assert_eq!(residuals, [1, 4, 5, 4]);
+}
+
+#[test]
+fn arithmetic_wraps() {
+    round_trip_u64(u64::MAX, &[0, u64::MAX, 1, u64::MAX - 1]);
+    round_trip_i64(i64::MIN, &[i64::MAX, i64::MIN, 0, -1]);
+    let (mode, _) = round_trip_i64(i64::MIN, &[i64::MIN, 0, i64::MAX]);
+    assert_eq!(mode, Mode::Sorted);
+}
+
+#[test]
+fn kernels_match_scalar_prefix_sum() {
+    // Lengths around the vector widths, so every kernel also runs its tail.
+    let mut x = 0x2545_f491_4f6c_dd1du64;
+    for len in [0, 1, 2, 3, 4, 5, 7, 8, 9, 1000] {
+        let steps: Vec<i64> = (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x % 2001) as i64 - 1000
+            })
+            .collect();
+        let mut series: Vec<i64> = steps
+            .iter()
+            .scan(1i64 << 40, |acc, &s| {
+                *acc = acc.wrapping_add(s);
+                Some(*acc)
+            })
+            .collect();
+        for sort in [false, true] {
+            if sort {
+                series.sort();
+            }
+            let mut residuals = vec![0; len];
+            let mode = delta::encode_i64(0, &series, &mut residuals);
+            assert_eq!(mode == Mode::Sorted, series.is_sorted());
+
+            let mut expected = vec![0; len];
+            Kernel::Scalar.prefix_sum(0, &residuals, mode, &mut expected);
+            for kernel in Kernel::available() {
+                let mut out = vec![0; len];
+                kernel.prefix_sum(0, &residuals, mode, &mut out);
+                assert_eq!(out, expected, "{} kernel, len {len}", kernel.name());
+            }
+            let expected: Vec<i64> = expected.into_iter().map(|v| v as i64).collect();
+            assert_eq!(expected, series);
+        }
+    }
+}
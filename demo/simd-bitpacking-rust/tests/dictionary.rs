@@ -0,0 +1,111 @@
+use simd_bitpacking_demo::dictionary::{self, Dictionary, DEFAULT_MAX_LEN};
+use simd_bitpacking_demo::Error;
+
+fn round_trip(values: &[u64], max_len: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    dictionary::encode_with_limit(values, max_len, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(
+        dictionary::decode(&out, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    assert_eq!(decoded, values);
+    out
+}
+
+#[test]
+fn ids_follow_first_appearance() {
+    let mut labels = Dictionary::new();
+    let ids: Vec<u32> = ["GET", "POST", "GET", "PUT", "POST"]
+        .map(String::from)
+        .iter()
+        .map(|method| labels.insert(method))
+        .collect();
+    assert_eq!(ids, [0, 1, 0, 2, 1]);
+    assert_eq!(labels.entries(), ["GET", "POST", "PUT"]);
+    assert_eq!(labels.id(&"PUT".to_string()), Some(2));
+    assert_eq!(labels.id(&"DELETE".to_string()), None);
+    assert_eq!(labels.get(1).map(String::as_str), Some("POST"));
+    assert_eq!(labels.get(3), None);
+}
+
+#[test]
+fn id_width_is_ceil_log2() {
+    let mut dictionary = Dictionary::new();
+    assert_eq!(dictionary.bit_width(), 0);
+    for (len, width) in [
+        (1, 0),
+        (2, 1),
+        (3, 2),
+        (4, 2),
+        (5, 3),
+        (8, 3),
+        (9, 4),
+        (256, 8),
+    ] {
+        while dictionary.len() < len {
+            dictionary.insert(&(dictionary.len() as u64));
+        }
+        assert_eq!(dictionary.bit_width(), width, "{len} entries");
+    }
+}
+
+#[test]
+fn status_codes() {
+    // Five distinct codes: a 40-byte dictionary and 3 bits per value.
+    let codes = [200, 200, 404, 200, 500, 301, 200, 503];
+    let values: Vec<u64> = (0..1000).map(|i| codes[i * 7 % 8]).collect();
+    let out = round_trip(&values, DEFAULT_MAX_LEN);
+    assert_eq!(out[0], 1);
+    assert_eq!(out[1..5], 5u32.to_le_bytes());
+    assert_eq!(out[5..13], 200u64.to_le_bytes());
+    assert_eq!(out.len(), 1 + 4 + 5 * 8 + 1000 * 3 / 8);
+}
+
+#[test]
+fn single_value_needs_no_ids() {
+    assert_eq!(round_trip(&[7; 500], DEFAULT_MAX_LEN).len(), 1 + 4 + 8);
+    round_trip(&[], DEFAULT_MAX_LEN);
+}
+
+#[test]
+fn falls_back_to_plain_past_the_limit() {
+    let values: Vec<u64> = (0..100).map(|i| i % 10).collect();
+    assert_eq!(round_trip(&values, 10)[0], 1);
+
+    let out = round_trip(&values, 9);
+    assert_eq!(out[0], 0);
+    assert_eq!(out.len(), 1 + 100 * 8);
+
+    let distinct: Vec<u64> = (0..DEFAULT_MAX_LEN as u64 + 1).collect();
+    let mut out = Vec::new();
+    dictionary::encode(&distinct, &mut out);
+    assert_eq!(out[0], 0);
+}
+
+#[test]
+fn malformed_input() {
+    let decode = |bytes: &[u8], count| dictionary::decode(bytes, count, &mut Vec::new());
+    assert_eq!(decode(&[], 0), Err(Error::Truncated));
+    assert_eq!(
+        decode(&[2], 0),
+        Err(Error::Corrupt("unknown dictionary mode"))
+    );
+    assert_eq!(decode(&[0, 1, 2, 3], 1), Err(Error::Truncated));
+
+    // Three entries, so ids take 2 bits and id 3 is out of range.
+    let mut out = Vec::new();
+    dictionary::encode(&[10, 20, 30, 30], &mut out);
+    assert_eq!(decode(&out, 4), Ok(out.len()));
+    assert_eq!(
+        decode(&out, 2),
+        Err(Error::Corrupt("dictionary longer than the column"))
+    );
+    let last = out.len() - 1;
+    out[last] |= 0b11;
+    assert_eq!(
+        decode(&out, 4),
+        Err(Error::Corrupt("id outside dictionary"))
+    );
+    assert_eq!(decode(&out[..last], 4), Err(Error::Truncated));
+}
@@ -0,0 +1,90 @@
+use simd_bitpacking_demo::dod::{self, TimestampDecoder, TimestampEncoder};
+use simd_bitpacking_demo::Error;
+
+// The walk-through from tsdb-compression-evolution.md.
+const WALK_THROUGH: [i64; 4] = [1643673600, 1643673660, 1643673722, 1643673780];
+
+fn bit_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:08b}")).collect()
+}
+
+#[test]
+fn walk_through_bits() {
+    let mut out = Vec::new();
+    dod::encode(&WALK_THROUGH, &mut out).unwrap();
+
+    let expected = [
+        format!("{:064b}", 1643673600u64), // t0: full 64 bits
+        format!("{:014b}", 60),            // t1: 14-bit delta
+        "10".to_owned() + "0000010",       // t2: D = 2
+        "10".to_owned() + "1111100",       // t3: D = -4
+    ]
+    .concat();
+    assert_eq!(expected.len(), 64 + 14 + 9 + 9);
+    assert_eq!(bit_string(&out), expected);
+}
+
+#[test]
+fn walk_through_round_trip() {
+    let mut out = Vec::new();
+    dod::encode(&WALK_THROUGH, &mut out).unwrap();
+
+    let mut decoded = Vec::new();
+    assert_eq!(dod::decode(&out, 4, &mut decoded), Ok(12));
+    assert_eq!(decoded, WALK_THROUGH);
+}
+
+#[test]
+fn every_bucket() {
+    // Deltas chosen so the delta-of-delta lands on each bucket's edges.
+    let mut ts = vec![1_000_000_000, 1_000_000_060];
+    let mut delta = 60;
+    for dod in [
+        0,
+        63,
+        -64,
+        64,
+        255,
+        -256,
+        256,
+        2047,
+        -2048,
+        2048,
+        i32::MAX as i64,
+        i32::MIN as i64,
+    ] {
+        delta += dod;
+        ts.push(ts.last().unwrap() + delta);
+    }
+
+    let mut encoder = TimestampEncoder::new();
+    for &t in &ts {
+        encoder.push(t).unwrap();
+    }
+    let bytes = encoder.finish();
+    let decoded: Result<Vec<i64>, Error> = TimestampDecoder::new(&bytes, ts.len()).collect();
+    assert_eq!(decoded.unwrap(), ts);
+}
+
+#[test]
+fn rejects_unencodable_deltas() {
+    let mut encoder = TimestampEncoder::new();
+    encoder.push(0).unwrap();
+    assert!(matches!(encoder.push(1 << 14), Err(Error::OutOfRange(_))));
+    encoder.push(10).unwrap();
+    assert!(matches!(
+        encoder.push(10 + 10 + (1 << 31)),
+        Err(Error::OutOfRange(_))
+    ));
+    assert_eq!(encoder.len(), 2);
+}
+
+#[test]
+fn truncated_stream() {
+    let mut out = Vec::new();
+    dod::encode(&WALK_THROUGH, &mut out).unwrap();
+    assert_eq!(
+        dod::decode(&out[..11], 4, &mut Vec::new()),
+        Err(Error::Truncated)
+    );
+}
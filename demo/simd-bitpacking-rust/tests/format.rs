@@ -0,0 +1,81 @@
+use simd_bitpacking_demo::format::{Block, Codec};
+use simd_bitpacking_demo::{frame_of_ref, Error};
+
+// FOR payload for [17, 18, 19]: min 17, width 2, residuals 0b10_01_00.
+const GOLDEN: [u8; 26] = [
+    0x42, 0x50, 0x41, 0x4b, // magic "BPAK"
+    0x01, // version
+    0x02, // codec: FOR
+    0x02, // bit width
+    0x00, // reserved
+    0x03, 0x00, 0x00, 0x00, // value count
+    0x06, 0x00, 0x00, 0x00, // payload length
+    0x11, 0x00, 0x00, 0x00, 0x02, 0x24, // payload
+    0xb4, 0xb4, 0xb1, 0xa9, // CRC-32C
+];
+
+#[test]
+fn golden_layout() {
+    let mut payload = Vec::new();
+    frame_of_ref::encode(&[17, 18, 19], &mut payload);
+    let block = Block {
+        codec: Codec::For,
+        bit_width: 2,
+        count: 3,
+        payload: &payload,
+    };
+    let mut out = Vec::new();
+    block.write(&mut out);
+    assert_eq!(out, GOLDEN);
+
+    let (read, len) = Block::read(&out).unwrap();
+    assert_eq!(read, block);
+    assert_eq!(len, GOLDEN.len());
+}
+
+#[test]
+fn codec_ids_round_trip() {
+    for codec in Codec::ALL {
+        assert_eq!(Codec::from_id(codec.id()), Some(codec));
+    }
+    assert_eq!(Codec::from_id(0), None);
+}
+
+#[test]
+fn every_flipped_bit_is_rejected() {
+    for bit in 0..GOLDEN.len() * 8 {
+        let mut corrupt = GOLDEN;
+        corrupt[bit / 8] ^= 1 << (bit % 8);
+        assert!(Block::read(&corrupt).is_err(), "flipped bit {bit}");
+    }
+}
+
+#[test]
+fn truncation_is_rejected() {
+    for len in 0..GOLDEN.len() {
+        assert_eq!(Block::read(&GOLDEN[..len]), Err(Error::Truncated));
+    }
+}
+
+#[test]
+fn consecutive_blocks() {
+    let mut out = Vec::new();
+    for payload in [&b"first"[..], b"", b"third"] {
+        let block = Block {
+            codec: Codec::Gorilla,
+            bit_width: 0,
+            count: payload.len() as u32,
+            payload,
+        };
+        block.write(&mut out);
+    }
+
+    let mut pos = 0;
+    let mut payloads = Vec::new();
+    while pos < out.len() {
+        let (block, len) = Block::read(&out[pos..]).unwrap();
+        payloads.push(block.payload);
+        pos += len;
+    }
+    assert_eq!(payloads, [&b"first"[..], b"", b"third"]);
+}
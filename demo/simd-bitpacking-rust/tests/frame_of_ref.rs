@@ -0,0 +1,105 @@
+use simd_bitpacking_demo::block::BLOCK_LEN;
+use simd_bitpacking_demo::frame_of_ref;
+use simd_bitpacking_demo::Error;
+
+fn round_trip(values: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    frame_of_ref::encode(values, &mut bytes);
+    let mut out = Vec::new();
+    assert_eq!(
+        frame_of_ref::decode(&bytes, values.len(), &mut out),
+        Ok(bytes.len())
+    );
+    assert_eq!(out, values);
+    bytes
+}
+
+fn round_trip64(values: &[u64]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    frame_of_ref::encode64(values, &mut bytes);
+    let mut out = Vec::new();
+    assert_eq!(
+        frame_of_ref::decode64(&bytes, values.len(), &mut out),
+        Ok(bytes.len())
+    );
+    assert_eq!(out, values);
+    bytes
+}
+
+// Second timestamps 10 s apart with some jitter, descending in places.
+fn timestamps(len: usize) -> Vec<u32> {
+    (0..len as u32)
+        .map(|i| 1_700_000_000 + (i % 64) * 10 + i % 7)
+        .collect()
+}
+
+#[test]
+fn header_holds_the_block_minimum() {
+    let values = timestamps(3 * BLOCK_LEN);
+    let bytes = round_trip(&values);
+    let mut pos = 0;
+    for block in values.chunks(BLOCK_LEN) {
+        let min = *block.iter().min().unwrap();
+        assert_eq!(bytes[pos..pos + 4], min.to_le_bytes());
+        let width = bytes[pos + 4];
+        pos += 5 + (block.len() * width as usize).div_ceil(8);
+    }
+    assert_eq!(pos, bytes.len());
+}
+
+#[test]
+fn residuals_pack_at_the_width_of_the_range() {
+    // Residuals span 0..=636, 10 bits rather than the 31 of the timestamps.
+    let values = timestamps(BLOCK_LEN);
+    let bytes = round_trip(&values);
+    assert_eq!(bytes[4], 10);
+    assert_eq!(bytes.len(), 5 + BLOCK_LEN * 10 / 8);
+
+    // A constant block is its header alone.
+    assert_eq!(round_trip(&[1_700_000_000; 50]).len(), 5);
+    assert_eq!(round_trip(&[u32::MAX, 0])[4], 32);
+
+    let values: Vec<u64> = values.iter().map(|&v| u64::from(v) << 20).collect();
+    let bytes = round_trip64(&values);
+    assert_eq!(bytes[8], 30);
+    assert_eq!(bytes[..8], (1_700_000_000u64 << 20).to_le_bytes());
+}
+
+#[test]
+fn full_u32_range() {
+    round_trip(&[u32::MAX, 0, u32::MAX - 1, 1]);
+    let bytes = round_trip(&[u32::MAX; 3]);
+    assert_eq!(bytes, [0xff, 0xff, 0xff, 0xff, 0]);
+    round_trip(&[]);
+}
+
+#[test]
+fn full_u64_range() {
+    round_trip64(&[u64::MAX, 0, u64::MAX - 1, 1]);
+    let bytes = round_trip64(&[u64::MAX; 3]);
+    assert_eq!(
+        bytes[..9],
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0]
+    );
+    round_trip64(&[]);
+}
+
+#[test]
+fn corrupt_input() {
+    let bytes = round_trip(&timestamps(200));
+    for len in [0, 3, 4, bytes.len() - 1] {
+        assert_eq!(
+            frame_of_ref::decode(&bytes[..len], 200, &mut Vec::new()),
+            Err(Error::Truncated),
+            "{len} bytes"
+        );
+    }
+    assert_eq!(
+        frame_of_ref::decode(&[0, 0, 0, 0, 33], 1, &mut Vec::new()),
+        Err(Error::InvalidBitWidth(33))
+    );
+    assert_eq!(
+        frame_of_ref::decode64(&[0, 0, 0, 0, 0, 0, 0, 0, 65], 1, &mut Vec::new()),
+        Err(Error::InvalidBitWidth(65))
+    );
+}
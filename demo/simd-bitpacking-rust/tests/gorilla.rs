@@ -0,0 +1,76 @@
+use simd_bitpacking_demo::gorilla::{self, GorillaDecoder, GorillaEncoder};
+use simd_bitpacking_demo::Error;
+
+fn round_trip(values: &[f64]) -> Vec<f64> {
+    let mut out = Vec::new();
+    gorilla::encode(values, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(
+        gorilla::decode(&out, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    decoded
+}
+
+fn bits(values: &[f64]) -> Vec<u64> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+#[test]
+fn go_demo_values() {
+    let values = [12.5, 12.5, 12.6, 12.6, 12.8];
+    assert_eq!(round_trip(&values), values);
+}
+
+#[test]
+fn notes_example_bits() {
+    // 12.0 ^ 12.5 = 0x0001000000000000: 15 leading zeros, one meaningful bit.
+    let mut encoder = GorillaEncoder::new();
+    encoder.push(12.0);
+    encoder.push(12.5);
+    let out = encoder.finish();
+
+    let stream: String = out.iter().map(|b| format!("{b:08b}")).collect();
+    let expected = format!("{:064b}", 12.0f64.to_bits()) + "11" + "01111" + "000001" + "1";
+    assert_eq!(&stream[..expected.len()], expected);
+    assert!(stream[expected.len()..].bytes().all(|b| b == b'0'));
+}
+
+#[test]
+fn special_values_are_bit_exact() {
+    let values = [
+        0.0,
+        -0.0,
+        f64::from_bits(0x7ff8_0000_0000_0001), // quiet NaN with payload
+        f64::from_bits(0x7ff0_0000_0000_0001), // signalling NaN
+        f64::from_bits(0xfff8_dead_beef_0000), // negative NaN
+        f64::NAN,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::MIN_POSITIVE / 3.0, // subnormal
+        f64::MAX,
+        -0.0,
+        1.0,
+        -1.0,
+    ];
+    assert_eq!(bits(&round_trip(&values)), bits(&values));
+}
+
+#[test]
+fn full_width_xor() {
+    // Flipping the sign and lowest bit needs all 64 meaningful bits.
+    let values = [
+        f64::from_bits(0),
+        f64::from_bits(0x8000_0000_0000_0001),
+        f64::from_bits(0),
+    ];
+    assert_eq!(bits(&round_trip(&values)), bits(&values));
+}
+
+#[test]
+fn truncated_stream() {
+    let mut out = Vec::new();
+    gorilla::encode(&[1.0, 2.0, 3.0], &mut out);
+    let decoded: Result<Vec<f64>, Error> = GorillaDecoder::new(&out[..9], 3).collect();
+    assert_eq!(decoded, Err(Error::Truncated));
+}
@@ -0,0 +1,110 @@
+use simd_bitpacking_demo::block::BLOCK_LEN;
+use simd_bitpacking_demo::kernel::Kernel;
+use simd_bitpacking_demo::{pack, unpack};
+
+// Deterministic values spread over the full u32 range, including bits above
+// the packed width that every kernel must ignore.
+fn block(seed: u32) -> [u32; BLOCK_LEN] {
+    let mut x = seed | 1;
+    std::array::from_fn(|_| {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        x
+    })
+}
+
+#[test]
+fn kernels_match_scalar_pack() {
+    for kernel in Kernel::available() {
+        for width in 0..=32u8 {
+            let values = block(width as u32 + 1);
+            let mut expected = [0u64; 64];
+            pack(&values, width, &mut expected);
+
+            let mut packed = [u64::MAX; 64];
+            kernel.pack_block(&values, width, &mut packed);
+            let words = 2 * width as usize;
+            assert_eq!(
+                packed[..words],
+                expected[..words],
+                "{} at width {width}",
+                kernel.name()
+            );
+        }
+    }
+}
+
+#[test]
+fn kernels_match_scalar_unpack() {
+    for kernel in Kernel::available() {
+        for width in 0..=32u8 {
+            let mut packed = [0u64; 64];
+            pack(&block(width as u32 + 7), width, &mut packed);
+            let mut expected = [0u32; BLOCK_LEN];
+            unpack(&packed, width, &mut expected);
+
+            let mut values = [u32::MAX; BLOCK_LEN];
+            kernel.unpack_block(&packed[..2 * width as usize], width, &mut values);
+            assert_eq!(values, expected, "{} at width {width}", kernel.name());
+        }
+    }
+}
+
+#[test]
+fn kernels_match_scalar_prefix_sum() {
+    use simd_bitpacking_demo::delta::{self, Mode};
+
+    // Odd length so every kernel also runs its scalar tail.
+    let values: Vec<i64> = block(11)
+        .iter()
+        .chain(&block(12))
+        .take(203)
+        .map(|&v| v as i32 as i64)
+        .collect();
+    let mut sorted = values.clone();
+    sorted.sort();
+
+    for series in [values, sorted] {
+        let mut residuals = vec![0; series.len()];
+        let mode = delta::encode_i64(-5, &series, &mut residuals);
+        assert_eq!(mode == Mode::Sorted, series.is_sorted() && series[0] >= -5);
+        for kernel in Kernel::available() {
+            let mut out = vec![0; series.len()];
+            kernel.prefix_sum(-5i64 as u64, &residuals, mode, &mut out);
+            let out: Vec<i64> = out.into_iter().map(|v| v as i64).collect();
+            assert_eq!(out, series, "{}", kernel.name());
+        }
+    }
+}
+
+#[test]
+fn kernels_match_scalar_streamvbyte() {
+    use simd_bitpacking_demo::streamvbyte;
+
+    // Every byte length, in runs and mixed, with a partial last quad.
+    let values: Vec<u32> = block(32)
+        .iter()
+        .chain(&block(9))
+        .chain(&block(17))
+        .chain(&block(1))
+        .enumerate()
+        .map(|(i, &v)| v >> (8 * (i / 7 % 4)))
+        .take(BLOCK_LEN * 4 - 3)
+        .collect();
+    let mut encoded = Vec::new();
+    streamvbyte::encode(&values, &mut encoded);
+    let (control, data) = encoded.split_at(values.len().div_ceil(4));
+
+    // Exact data, so that every kernel also runs its scalar tail, then
+    // trailing bytes that let the shuffles run to the end.
+    let padded = [data, &[0xff; 32]].concat();
+    for data in [data, &padded] {
+        for kernel in Kernel::available() {
+            let mut out = vec![0; values.len()];
+            let read = kernel.streamvbyte_decode(control, data, &mut out);
+            assert_eq!(read, encoded.len() - control.len(), "{}", kernel.name());
+            assert_eq!(out, values, "{}", kernel.name());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use simd_bitpacking_demo::leb128::{self, MAX_LEN};
+use simd_bitpacking_demo::Error;
+
+fn varint(value: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    leb128::write_u64(value, &mut out);
+    assert_eq!(leb128::read_u64(&out), Ok((value, out.len())));
+    out
+}
+
+#[test]
+fn protobuf_examples() {
+    assert_eq!(varint(0), [0x00]);
+    assert_eq!(varint(1), [0x01]);
+    assert_eq!(varint(127), [0x7f]);
+    assert_eq!(varint(128), [0x80, 0x01]);
+    assert_eq!(varint(150), [0x96, 0x01]);
+    assert_eq!(varint(300), [0xac, 0x02]);
+    assert_eq!(varint(u64::MAX).len(), MAX_LEN);
+    assert_eq!(varint(u64::MAX)[MAX_LEN - 1], 0x01);
+}
+
+#[test]
+fn lengths_grow_every_seven_bits() {
+    for bits in 1..=64u32 {
+        let value = u64::MAX >> (64 - bits);
+        assert_eq!(varint(value).len(), bits.div_ceil(7) as usize);
+    }
+}
+
+#[test]
+fn zigzag() {
+    for (value, encoded) in [(0, 0x00), (-1, 0x01), (1, 0x02), (-2, 0x03), (63, 0x7e)] {
+        let mut out = Vec::new();
+        leb128::write_i64(value, &mut out);
+        assert_eq!(out, [encoded]);
+        assert_eq!(leb128::read_i64(&out), Ok((value, 1)));
+    }
+    let mut out = Vec::new();
+    leb128::write_i64(i64::MIN, &mut out);
+    assert_eq!(leb128::read_i64(&out), Ok((i64::MIN, MAX_LEN)));
+}
+
+#[test]
+fn redundant_zero_groups_are_accepted() {
+    assert_eq!(leb128::read_u64(&[0x81, 0x80, 0x00]), Ok((1, 3)));
+}
+
+#[test]
+fn malformed_varints() {
+    assert_eq!(leb128::read_u64(&[]), Err(Error::Truncated));
+    assert_eq!(leb128::read_u64(&[0x80, 0x80]), Err(Error::Truncated));
+    let mut too_long = vec![0xff; MAX_LEN - 1];
+    too_long.push(0x02);
+    assert_eq!(
+        leb128::read_u64(&too_long),
+        Err(Error::Corrupt("varint exceeds 64 bits"))
+    );
+    assert_eq!(
+        leb128::read_u64(&[0x80; 11]),
+        Err(Error::Corrupt("varint exceeds 64 bits"))
+    );
+}
+
+#[test]
+fn series() {
+    let values = [0, 300, u64::MAX, 1 << 35, 5];
+    let mut out = Vec::new();
+    leb128::encode(&values, &mut out);
+    assert_eq!(out.len(), 1 + 2 + 10 + 6 + 1);
+    let mut decoded = Vec::new();
+    assert_eq!(leb128::decode(&out, 5, &mut decoded), Ok(out.len()));
+    assert_eq!(decoded, values);
+    assert_eq!(
+        leb128::decode(&out, 6, &mut Vec::new()),
+        Err(Error::Truncated)
+    );
+}
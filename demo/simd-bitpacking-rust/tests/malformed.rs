@@ -0,0 +1,203 @@
+// Decoders must reject arbitrary bytes with an error: never a panic, an
+// out-of-bounds read or an unbounded allocation.
+
+use proptest::prelude::*;
+use simd_bitpacking_demo::adaptive::{self, Selector};
+use simd_bitpacking_demo::aggregate::{self, Aggregate};
+use simd_bitpacking_demo::bits::BitReader;
+use simd_bitpacking_demo::format::Block;
+use simd_bitpacking_demo::predicate::{self, Cmp};
+use simd_bitpacking_demo::stream::Decoder;
+use simd_bitpacking_demo::Error;
+use simd_bitpacking_demo::{
+    alp, block, chimp, delta, dictionary, dod, frame_of_ref, gorilla, leb128, parquet_rle, pfor,
+    simple8b, streamvbyte,
+};
+
+fn input() -> impl Strategy<Value = (Vec<u8>, usize)> {
+    (prop::collection::vec(any::<u8>(), 0..2048), 0..2048usize)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn block_decoders((bytes, count) in input(), start in 0..2048usize, len in 0..300usize) {
+        let _ = block::decode(&bytes, count, &mut Vec::new());
+        let _ = frame_of_ref::decode(&bytes, count, &mut Vec::new());
+        let _ = frame_of_ref::decode64(&bytes, count, &mut Vec::new());
+        let _ = pfor::decode(&bytes, count, &mut Vec::new());
+        let _ = alp::decode(&bytes, count, &mut Vec::new());
+        let _ = simple8b::decode(&bytes, count, &mut Vec::new());
+        let _ = leb128::decode(&bytes, count, &mut Vec::new());
+        let _ = streamvbyte::decode(&bytes, count, &mut Vec::new());
+        let _ = parquet_rle::decode(&bytes, count, &mut Vec::new());
+        let _ = dictionary::decode(&bytes, count, &mut Vec::new());
+        let _ = delta::decode_series(&bytes, count, &mut Vec::new());
+        let _ = adaptive::decode(&bytes, count, &mut Vec::new());
+
+        let start = start.min(count);
+        let len = len.min(count - start);
+        let _ = block::decode_range(&bytes, count, start, len, &mut Vec::new());
+    }
+
+    #[test]
+    fn bitstream_decoders((bytes, count) in input()) {
+        let _ = dod::decode(&bytes, count, &mut Vec::new());
+        let _ = gorilla::decode(&bytes, count, &mut Vec::new());
+        let _ = chimp::decode(&bytes, count, &mut Vec::new());
+        let _ = chimp::decode128(&bytes, count, &mut Vec::new());
+
+        let mut reader = BitReader::new(&bytes);
+        for n in (0..=64).cycle().take(count) {
+            if reader.read_bits(n).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn scans((bytes, count) in input(), threshold: u32) {
+        let _ = predicate::select_blocks(&bytes, count, Cmp::Ge, threshold, &mut Vec::new());
+        let _ = aggregate::blocks(&bytes, count, &mut Aggregate::default());
+        let _ = aggregate::frame_of_ref(&bytes, count, &mut Aggregate::default());
+        let _ = aggregate::frame_of_ref64(&bytes, count, &mut Aggregate::default());
+    }
+
+    #[test]
+    fn containers(bytes in prop::collection::vec(any::<u8>(), 0..2048)) {
+        let _ = Block::read(&bytes);
+        let mut decoder = Decoder::new(bytes.as_slice());
+        decoder.by_ref().count();
+    }
+}
+
+#[test]
+fn rle_runs_expand_only_to_their_length() {
+    // Width 1, then one RLE run of 1000 ones: 4 bytes for 1000 values.
+    let input = [1, 0xd0, 0x0f, 1];
+    let mut out = Vec::new();
+    assert_eq!(parquet_rle::decode(&input, 1000, &mut out), Ok(4));
+    assert_eq!(out, [1; 1000]);
+
+    // A count past the run finds no more runs instead of repeating it.
+    let mut out = Vec::new();
+    assert_eq!(
+        parquet_rle::decode(&input, 1 << 40, &mut out),
+        Err(Error::Truncated)
+    );
+    assert!(out.len() <= 1000);
+    assert_eq!(
+        parquet_rle::decode(&input, 999, &mut Vec::new()),
+        Err(Error::Corrupt("run exceeds value count"))
+    );
+}
+
+// Valid encodings with a few bytes flipped get past the headers that random
+// bytes almost never satisfy.
+fn corrupt(mut bytes: Vec<u8>, flips: &[(usize, u8)]) -> Vec<u8> {
+    if !bytes.is_empty() {
+        for &(at, xor) in flips {
+            let len = bytes.len();
+            bytes[at % len] ^= xor;
+        }
+    }
+    bytes
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn corrupted_encodings(
+        values in prop::collection::vec(any::<u64>(), 0..600),
+        shift in 0..64u32,
+        flips in prop::collection::vec((any::<usize>(), 1..=255u8), 1..4),
+        extra in 0..300usize,
+    ) {
+        let values64: Vec<u64> = values.iter().map(|v| v >> shift).collect();
+        let values32: Vec<u32> = values64.iter().map(|&v| v as u32).collect();
+        let count = values.len() + extra;
+
+        let mut bytes = Vec::new();
+        block::encode(&values32, &mut bytes);
+        let bytes = corrupt(bytes, &flips);
+        let _ = block::decode(&bytes, count, &mut Vec::new());
+        let _ = predicate::select_blocks(&bytes, count, Cmp::Lt, 1 << 20, &mut Vec::new());
+        let _ = aggregate::blocks(&bytes, count, &mut Aggregate::default());
+
+        let mut bytes = Vec::new();
+        frame_of_ref::encode(&values32, &mut bytes);
+        let bytes = corrupt(bytes, &flips);
+        let _ = frame_of_ref::decode(&bytes, count, &mut Vec::new());
+        let _ = aggregate::frame_of_ref(&bytes, count, &mut Aggregate::default());
+
+        let mut bytes = Vec::new();
+        frame_of_ref::encode64(&values64, &mut bytes);
+        let bytes = corrupt(bytes, &flips);
+        let _ = frame_of_ref::decode64(&bytes, count, &mut Vec::new());
+        let _ = aggregate::frame_of_ref64(&bytes, count, &mut Aggregate::default());
+
+        let mut bytes = Vec::new();
+        pfor::encode(&values32, &mut bytes);
+        let _ = pfor::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let small: Vec<u64> = values64.iter().map(|&v| v & simple8b::MAX_VALUE).collect();
+        let mut bytes = Vec::new();
+        simple8b::encode(&small, &mut bytes).unwrap();
+        let _ = simple8b::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        leb128::encode(&values64, &mut bytes);
+        let _ = leb128::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        streamvbyte::encode(&values32, &mut bytes);
+        let _ = streamvbyte::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        parquet_rle::encode(&values32, &mut bytes);
+        let _ = parquet_rle::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let labels: Vec<u64> = values64.iter().map(|&v| v % 37).collect();
+        let mut bytes = Vec::new();
+        dictionary::encode(&labels, &mut bytes);
+        let _ = dictionary::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        delta::encode_series(&values64, &mut bytes);
+        let _ = delta::decode_series(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        Selector::new().encode(&labels, &mut bytes);
+        let _ = adaptive::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let floats: Vec<f64> = values64.iter().map(|&v| v as f64).collect();
+        let mut bytes = Vec::new();
+        gorilla::encode(&floats, &mut bytes);
+        let _ = gorilla::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        chimp::encode(&floats, &mut bytes);
+        let _ = chimp::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut bytes = Vec::new();
+        chimp::encode128(&floats, &mut bytes);
+        let _ = chimp::decode128(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let decimals: Vec<f64> = values64.iter().map(|&v| (v % 100_000) as f64 / 100.0).collect();
+        let mut bytes = Vec::new();
+        alp::encode(&decimals, &mut bytes);
+        let _ = alp::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let timestamps: Vec<i64> = (0..values.len() as i64).map(|i| i * 1000 + (values64[i as usize] % 7) as i64).collect();
+        let mut bytes = Vec::new();
+        dod::encode(&timestamps, &mut bytes).unwrap();
+        let _ = dod::decode(&corrupt(bytes, &flips), count, &mut Vec::new());
+
+        let mut encoder = simd_bitpacking_demo::stream::Encoder::new(Vec::new());
+        encoder.push_slice(&values64).unwrap();
+        let bytes = corrupt(encoder.finish().unwrap(), &flips);
+        Decoder::new(bytes.as_slice()).count();
+    }
+}
@@ -0,0 +1,94 @@
+use simd_bitpacking_demo::parquet_rle;
+use simd_bitpacking_demo::Error;
+
+fn runs(values: &[u32], bit_width: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    parquet_rle::encode_runs(values, bit_width, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(
+        parquet_rle::decode_runs(&out, bit_width, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    assert_eq!(decoded, values);
+    out
+}
+
+#[test]
+fn spec_bit_packed_example() {
+    // 0..=7 at width 3, from the Parquet encodings spec.
+    let values: Vec<u32> = (0..8).collect();
+    assert_eq!(
+        runs(&values, 3),
+        [0x03, 0b1000_1000, 0b1100_0110, 0b1111_1010]
+    );
+}
+
+#[test]
+fn rle_runs() {
+    // Header 100 << 1 = 200 as a varint, then the value in one byte.
+    assert_eq!(runs(&[4; 100], 3), [0xc8, 0x01, 0x04]);
+    // Widths above 8 store the value in ceil(width / 8) bytes.
+    assert_eq!(runs(&[0xabc; 10], 12), [0x14, 0xbc, 0x0a]);
+    assert_eq!(runs(&[0x1_0000; 8], 17), [0x10, 0x00, 0x00, 0x01]);
+    // Width 0 has no value bytes at all.
+    assert_eq!(runs(&[0; 20], 0), [0x28]);
+}
+
+#[test]
+fn run_completes_the_pending_group() {
+    // 1, 2, 3 and the first five 7s fill a group; the other fifteen 7s are
+    // an RLE run.
+    let mut values = vec![1, 2, 3];
+    values.extend([7; 20]);
+    assert_eq!(runs(&values, 3), [0x03, 0xd1, 0xfe, 0xff, 0x1e, 0x07]);
+    // Seven repeats stay bit-packed.
+    assert_eq!(runs(&[2; 7], 2)[0], 0x03);
+}
+
+#[test]
+fn last_group_is_zero_padded() {
+    assert_eq!(runs(&[5; 3], 3), [0x03, 0x6d, 0x01, 0x00]);
+}
+
+#[test]
+fn long_bit_packed_runs() {
+    // 125 groups need a two-byte header; the 1000 values span several
+    // blocks of the pack loop.
+    let values: Vec<u32> = (0..1000).map(|i| i * 7 % 1000).collect();
+    let out = runs(&values, 10);
+    assert_eq!(out[..2], [0xfb, 0x01]);
+    assert_eq!(out.len(), 2 + 1000 * 10 / 8);
+}
+
+#[test]
+fn dictionary_layout() {
+    let values: Vec<u32> = (0..8).collect();
+    let mut out = Vec::new();
+    parquet_rle::encode(&values, &mut out);
+    assert_eq!(out, [3, 0x03, 0x88, 0xc6, 0xfa]);
+    let mut decoded = Vec::new();
+    assert_eq!(parquet_rle::decode(&out, 8, &mut decoded), Ok(5));
+    assert_eq!(decoded, values);
+}
+
+#[test]
+fn malformed_runs() {
+    let decode =
+        |bytes: &[u8], width, count| parquet_rle::decode_runs(bytes, width, count, &mut Vec::new());
+    assert_eq!(decode(&[0x03], 33, 1), Err(Error::InvalidBitWidth(33)));
+    assert_eq!(
+        decode(&[0x10, 0x01], 3, 7),
+        Err(Error::Corrupt("run exceeds value count"))
+    );
+    assert_eq!(
+        decode(&[0x10, 0x09], 3, 8),
+        Err(Error::Corrupt("run value exceeds bit width"))
+    );
+    assert_eq!(decode(&[0x03, 0x88, 0xc6], 3, 8), Err(Error::Truncated));
+    assert_eq!(decode(&[0x10], 3, 8), Err(Error::Truncated));
+    assert_eq!(decode(&[0x80], 3, 8), Err(Error::Truncated));
+    assert_eq!(
+        parquet_rle::decode(&[], 1, &mut Vec::new()),
+        Err(Error::Truncated)
+    );
+}
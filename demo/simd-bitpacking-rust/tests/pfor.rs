@@ -0,0 +1,80 @@
+use simd_bitpacking_demo::block::BLOCK_LEN;
+use simd_bitpacking_demo::pfor::{self, coverage_width};
+use simd_bitpacking_demo::Error;
+
+fn round_trip(values: &[u32]) -> (Vec<u8>, Vec<usize>) {
+    let mut out = Vec::new();
+    let exceptions = pfor::encode(values, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(
+        pfor::decode(&out, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    assert_eq!(decoded, values);
+    (out, exceptions)
+}
+
+#[test]
+fn one_outlier_is_one_exception() {
+    let mut values: Vec<u32> = (0..BLOCK_LEN as u32).map(|i| i % 16).collect();
+    values[37] = 1 << 20;
+    let (out, exceptions) = round_trip(&values);
+    assert_eq!(exceptions, [1]);
+    // The block packs at the 4 bits of the others, not the outlier's 21.
+    assert_eq!(out[..2], [4, 1]);
+    assert!(out.len() < 21 * BLOCK_LEN / 8);
+
+    let (out, exceptions) = round_trip(&values[38..]);
+    assert_eq!(exceptions, [0]);
+    assert_eq!(out[..2], [4, 0]);
+}
+
+#[test]
+fn exceptions_are_counted_per_block() {
+    let mut values = vec![3; 3 * BLOCK_LEN + 10];
+    values[5] = u32::MAX;
+    values[2 * BLOCK_LEN] = 1 << 30;
+    values[2 * BLOCK_LEN + 1] = 1 << 31;
+    let (_, exceptions) = round_trip(&values);
+    assert_eq!(exceptions, [1, 0, 2, 0]);
+    assert_eq!(round_trip(&[]).1, []);
+}
+
+#[test]
+fn coverage_width_at_the_boundary() {
+    // 90% of 128 rounds up to 116 values.
+    let mut block = [7u32; BLOCK_LEN];
+    block[116..].fill(1000);
+    assert_eq!(coverage_width(&block), 3);
+    block[115] = 1000;
+    assert_eq!(coverage_width(&block), 10);
+
+    // 9 of 10 values is exactly 90%.
+    let mut block = [1u32; 10];
+    block[9] = u32::MAX;
+    assert_eq!(coverage_width(&block), 1);
+    block[8] = u32::MAX;
+    assert_eq!(coverage_width(&block), 32);
+
+    assert_eq!(coverage_width(&[0; BLOCK_LEN]), 0);
+    assert_eq!(coverage_width(&[]), 0);
+}
+
+#[test]
+fn corrupt_input() {
+    // Width 32 leaves no high bits for an exception to patch.
+    let mut input = vec![32, 1];
+    input.extend_from_slice(&7u32.to_le_bytes());
+    assert_eq!(
+        pfor::decode(&input, 1, &mut Vec::new()),
+        Err(Error::Corrupt("exceptions in a full-width block"))
+    );
+    assert_eq!(
+        pfor::decode(&[4, 2, 0], 1, &mut Vec::new()),
+        Err(Error::Corrupt("exception count exceeds block"))
+    );
+    assert_eq!(
+        pfor::decode(&[4], 1, &mut Vec::new()),
+        Err(Error::Truncated)
+    );
+}
@@ -0,0 +1,108 @@
+use simd_bitpacking_demo::block::{self, BLOCK_LEN};
+use simd_bitpacking_demo::kernel::Kernel;
+use simd_bitpacking_demo::predicate::{self, Cmp};
+use simd_bitpacking_demo::{pack, packed_len};
+
+// Deterministic values masked to `width` bits.
+fn values(seed: u32, len: usize, width: u8) -> Vec<u32> {
+    let mut x = seed | 1;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            (x as u64 & ((1u64 << width) - 1)) as u32
+        })
+        .collect()
+}
+
+fn expected(values: &[u32], cmp: Cmp, threshold: u32) -> Vec<u64> {
+    let mut mask = vec![0u64; values.len().div_ceil(64)];
+    for (i, &v) in values.iter().enumerate() {
+        if cmp.eval(v, threshold) {
+            mask[i / 64] |= 1 << (i % 64);
+        }
+    }
+    mask
+}
+
+// Thresholds around the edges of the width's range, plus values that occur.
+fn thresholds(values: &[u32], width: u8) -> Vec<u32> {
+    let max = ((1u64 << width) - 1) as u32;
+    let mut t = vec![0, 1, max / 2, max.wrapping_sub(1), max, max.wrapping_add(1)];
+    t.extend_from_slice(&values[..3]);
+    t.push(u32::MAX);
+    t
+}
+
+#[test]
+fn kernels_match_scalar_select() {
+    for kernel in Kernel::available() {
+        for width in 0..=32u8 {
+            let values = values(width as u32 + 1, BLOCK_LEN, width);
+            let mut packed = [0u64; 64];
+            pack(&values, width, &mut packed);
+            for threshold in thresholds(&values, width) {
+                for cmp in Cmp::ALL {
+                    assert_eq!(
+                        kernel.select_block(&packed, width, cmp, threshold)[..],
+                        expected(&values, cmp, threshold),
+                        "{} at width {width}: value {cmp:?} {threshold}",
+                        kernel.name()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn select_on_packed_stream() {
+    for width in [0, 1, 7, 13, 32] {
+        let values = values(99, 3 * BLOCK_LEN + 45, width);
+        let mut packed = vec![0u64; packed_len(values.len(), width)];
+        pack(&values, width, &mut packed);
+        for threshold in thresholds(&values, width) {
+            for cmp in Cmp::ALL {
+                let mut mask = vec![u64::MAX; values.len().div_ceil(64)];
+                predicate::select(&packed, width, values.len(), cmp, threshold, &mut mask);
+                assert_eq!(mask, expected(&values, cmp, threshold), "width {width}");
+            }
+        }
+    }
+}
+
+#[test]
+fn select_on_blocks_with_mixed_widths() {
+    // Each block has its own width, so some blocks are decided by width alone.
+    let values: Vec<u32> = (0..7)
+        .flat_map(|b| values(b + 1, BLOCK_LEN, [0, 3, 8, 17, 32, 5, 1][b as usize]))
+        .take(6 * BLOCK_LEN + 100)
+        .collect();
+    let mut bytes = Vec::new();
+    block::encode(&values, &mut bytes);
+
+    for threshold in [0, 1, 7, 8, 200, 70_000, u32::MAX] {
+        for cmp in Cmp::ALL {
+            let mut mask = Vec::new();
+            let read = predicate::select_blocks(&bytes, values.len(), cmp, threshold, &mut mask);
+            assert_eq!(read, Ok(bytes.len()));
+            assert_eq!(
+                mask,
+                expected(&values, cmp, threshold),
+                "{cmp:?} {threshold}"
+            );
+        }
+    }
+}
+
+#[test]
+fn select_on_truncated_blocks() {
+    let values = values(5, 200, 9);
+    let mut bytes = Vec::new();
+    block::encode(&values, &mut bytes);
+    let mut mask = Vec::new();
+    assert!(
+        predicate::select_blocks(&bytes[..bytes.len() - 1], 200, Cmp::Gt, 3, &mut mask).is_err()
+    );
+}
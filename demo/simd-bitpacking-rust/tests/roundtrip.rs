@@ -0,0 +1,360 @@
+// Property-based round trips for every codec, across bit widths and lengths
+// that cover empty input, partial blocks and several full blocks.
+
+use proptest::prelude::*;
+use simd_bitpacking_demo::adaptive::{self, Selector};
+use simd_bitpacking_demo::bits::{BitReader, BitWriter};
+use simd_bitpacking_demo::block::BLOCK_LEN;
+use simd_bitpacking_demo::delta::{self, Mode};
+use simd_bitpacking_demo::format::{Block, Codec};
+use simd_bitpacking_demo::kernel::Kernel;
+use simd_bitpacking_demo::stream::{Decoder, Encoder};
+use simd_bitpacking_demo::{
+    alp, block, chimp, dictionary, dod, frame_of_ref, gorilla, leb128, pack, pack64, packed_len,
+    parquet_rle, pfor, simple8b, streamvbyte, unpack, unpack64,
+};
+
+// Values that fit in a random width, so every width gets exercised.
+fn values32(max_len: usize) -> impl Strategy<Value = (u8, Vec<u32>)> {
+    (0..=32u8).prop_flat_map(move |width| {
+        let max = ((1u64 << width) - 1) as u32;
+        (Just(width), prop::collection::vec(0..=max, 0..max_len))
+    })
+}
+
+fn values64(max_len: usize) -> impl Strategy<Value = (u8, Vec<u64>)> {
+    (0..=64u8).prop_flat_map(move |width| {
+        let max = if width == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - width)
+        };
+        (Just(width), prop::collection::vec(0..=max, 0..max_len))
+    })
+}
+
+proptest! {
+    #[test]
+    fn pack_unpack((width, values) in values32(600)) {
+        let mut packed = vec![0; packed_len(values.len(), width)];
+        pack(&values, width, &mut packed);
+        let mut out = vec![0; values.len()];
+        unpack(&packed, width, &mut out);
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn pack_unpack64((width, values) in values64(600)) {
+        let mut packed = vec![0; packed_len(values.len(), width)];
+        pack64(&values, width, &mut packed);
+        let mut out = vec![0; values.len()];
+        unpack64(&packed, width, &mut out);
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn kernels((width, values) in values32(BLOCK_LEN + 1)) {
+        let mut block = [0u32; BLOCK_LEN];
+        for (slot, &v) in block.iter_mut().zip(values.iter().cycle()) {
+            *slot = v;
+        }
+        for kernel in Kernel::available() {
+            let mut packed = [0u64; 64];
+            kernel.pack_block(&block, width, &mut packed);
+            for other in Kernel::available() {
+                let mut out = [0u32; BLOCK_LEN];
+                other.unpack_block(&packed, width, &mut out);
+                prop_assert_eq!(out, block, "{} -> {}", kernel.name(), other.name());
+            }
+        }
+    }
+
+    #[test]
+    fn block_codec((_, values) in values32(1000)) {
+        let mut bytes = Vec::new();
+        block::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(block::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn frame_of_ref_codec(values in prop::collection::vec(any::<u32>(), 0..1000)) {
+        let mut bytes = Vec::new();
+        frame_of_ref::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(frame_of_ref::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn frame_of_ref64_codec((_, values) in values64(1000), offset: u64) {
+        let values: Vec<u64> = values.iter().map(|v| v.wrapping_add(offset)).collect();
+        let mut bytes = Vec::new();
+        frame_of_ref::encode64(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(frame_of_ref::decode64(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn pfor_codec(
+        (_, mut values) in values32(1000),
+        outliers in prop::collection::vec((any::<usize>(), any::<u32>()), 0..20),
+    ) {
+        let len = values.len();
+        for (at, v) in outliers.into_iter().filter(|_| len > 0) {
+            values[at % len] = v;
+        }
+        let mut bytes = Vec::new();
+        pfor::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(pfor::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn delta_u64(base: u64, values in prop::collection::vec(any::<u64>(), 0..600), sorted: bool) {
+        let mut values = values;
+        if sorted {
+            values.sort_unstable();
+        }
+        let mut residuals = vec![0; values.len()];
+        let mode = delta::encode_u64(base, &values, &mut residuals);
+        if sorted && values.first().is_none_or(|&first| first >= base) {
+            prop_assert_eq!(mode, Mode::Sorted);
+        }
+        let mut out = vec![0; values.len()];
+        delta::decode_u64(base, &residuals, mode, &mut out);
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn delta_series(values in prop::collection::vec(any::<u64>(), 0..1000), sorted: bool) {
+        let mut values = values;
+        if sorted {
+            values.sort_unstable();
+        }
+        let mut bytes = Vec::new();
+        delta::encode_series(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(delta::decode_series(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn delta_i64(base: i64, values in prop::collection::vec(any::<i64>(), 0..600)) {
+        let mut residuals = vec![0; values.len()];
+        let mode = delta::encode_i64(base, &values, &mut residuals);
+        let mut out = vec![0; values.len()];
+        delta::decode_i64(base, &residuals, mode, &mut out);
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn dod_codec(
+        start in -(1i64 << 62)..(1i64 << 62),
+        first in 0..16384i64,
+        dods in prop::collection::vec(-(1i64 << 31)..(1i64 << 31), 0..600),
+    ) {
+        let mut values = vec![start, start + first];
+        let mut delta = first;
+        for dod in dods {
+            delta += dod;
+            values.push(values[values.len() - 1].wrapping_add(delta));
+        }
+        let mut bytes = Vec::new();
+        dod::encode(&values, &mut bytes).unwrap();
+        let mut out = Vec::new();
+        prop_assert_eq!(dod::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn gorilla_codec(bits in prop::collection::vec(any::<u64>(), 0..600)) {
+        let values: Vec<f64> = bits.iter().map(|&b| f64::from_bits(b)).collect();
+        let mut bytes = Vec::new();
+        gorilla::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(gorilla::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        let out: Vec<u64> = out.iter().map(|v| v.to_bits()).collect();
+        prop_assert_eq!(out, bits);
+    }
+
+    #[test]
+    fn chimp_codecs(bits in prop::collection::vec(any::<u64>(), 0..600), repeats in 0..600usize) {
+        // Repeat earlier values so that Chimp128 finds matches in its ring.
+        let mut values: Vec<f64> = bits.iter().map(|&b| f64::from_bits(b)).collect();
+        for i in 0..repeats.min(values.len()) {
+            values.push(values[i * 7919 % values.len()]);
+        }
+        let expected: Vec<u64> = values.iter().map(|v| v.to_bits()).collect();
+
+        let mut bytes = Vec::new();
+        chimp::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(chimp::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out.iter().map(|v| v.to_bits()).collect::<Vec<_>>(), expected.clone());
+
+        let mut bytes = Vec::new();
+        chimp::encode128(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(chimp::decode128(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out.iter().map(|v| v.to_bits()).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn alp_codec(
+        decimals in prop::collection::vec((any::<i32>(), 0..=6i32), 0..3000),
+        bits in prop::collection::vec((any::<u64>(), any::<prop::sample::Index>()), 0..40),
+    ) {
+        // Decimals with up to six places, plus arbitrary values as exceptions.
+        let mut values: Vec<f64> = decimals.iter().map(|&(d, e)| d as f64 / 10f64.powi(e)).collect();
+        for &(b, i) in &bits {
+            if !values.is_empty() {
+                let i = i.index(values.len());
+                values[i] = f64::from_bits(b);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        alp::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(alp::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        let out: Vec<u64> = out.iter().map(|v| v.to_bits()).collect();
+        prop_assert_eq!(out, values.iter().map(|v| v.to_bits()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn simple8b_codec(
+        (_, values) in values64(1000),
+        runs in prop::collection::vec((any::<prop::sample::Index>(), 0..500usize), 0..4),
+    ) {
+        // Runs of ones exercise the run-length selectors.
+        let mut values: Vec<u64> = values.iter().map(|&v| v & simple8b::MAX_VALUE).collect();
+        for &(at, len) in &runs {
+            let at = at.index(values.len() + 1);
+            values.splice(at..at, std::iter::repeat_n(1, len));
+        }
+
+        let mut bytes = Vec::new();
+        simple8b::encode(&values, &mut bytes).unwrap();
+        let mut out = Vec::new();
+        prop_assert_eq!(simple8b::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn varint_codecs((_, values) in values64(1000)) {
+        let mut bytes = Vec::new();
+        leb128::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(leb128::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(&out, &values);
+
+        let values: Vec<u32> = values.iter().map(|&v| v as u32).collect();
+        let mut bytes = Vec::new();
+        streamvbyte::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(streamvbyte::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn parquet_rle_codec(
+        (width, values) in values32(1000),
+        runs in prop::collection::vec((any::<prop::sample::Index>(), 0..40usize), 0..8),
+    ) {
+        // Repeats of every length around the RLE threshold.
+        let mut values = values;
+        for &(at, len) in &runs {
+            let at = at.index(values.len() + 1);
+            let v = values.get(at).copied().unwrap_or(0);
+            values.splice(at..at, std::iter::repeat_n(v, len));
+        }
+
+        let mut bytes = Vec::new();
+        parquet_rle::encode_runs(&values, width, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(parquet_rle::decode_runs(&bytes, width, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(&out, &values);
+
+        let mut bytes = Vec::new();
+        parquet_rle::encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(parquet_rle::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn dictionary_codec(
+        (_, values) in values64(1000),
+        distinct in 1..300u64,
+        limit in 0..300usize,
+    ) {
+        // Few distinct values, so both the dictionary and the fallback run.
+        let values: Vec<u64> = values.iter().map(|&v| v % distinct).collect();
+        let mut bytes = Vec::new();
+        dictionary::encode_with_limit(&values, limit, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(dictionary::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn adaptive_codec(
+        (_, values) in values64(10_000),
+        distinct in 1..u64::MAX,
+        offset: u64,
+        sorted: bool,
+    ) {
+        // Several frames; the modulus, offset and order vary which codec wins.
+        let mut values: Vec<u64> = values.iter().map(|&v| (v % distinct).wrapping_add(offset)).collect();
+        if sorted {
+            values.sort_unstable();
+        }
+        let mut bytes = Vec::new();
+        Selector::new().encode(&values, &mut bytes);
+        let mut out = Vec::new();
+        prop_assert_eq!(adaptive::decode(&bytes, values.len(), &mut out), Ok(bytes.len()));
+        prop_assert_eq!(out, values);
+    }
+
+    #[test]
+    fn bitstream(fields in prop::collection::vec((any::<u64>(), 0..=64u32), 0..300)) {
+        let mut writer = BitWriter::new();
+        for &(value, n) in &fields {
+            writer.write_bits(value, n);
+        }
+        let bytes = writer.finish();
+        let mut reader = BitReader::new(&bytes);
+        for &(value, n) in &fields {
+            let expected = if n == 64 { value } else { value & ((1 << n) - 1) };
+            prop_assert_eq!(reader.read_bits(n), Ok(expected));
+        }
+        prop_assert!(reader.remaining() < 8);
+    }
+
+    #[test]
+    fn stream_codec((_, values) in values64(1000)) {
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.push_slice(&values).unwrap();
+        let bytes = encoder.finish().unwrap();
+        let mut decoder = Decoder::new(bytes.as_slice());
+        prop_assert!(decoder.by_ref().eq(values.iter().copied()));
+        prop_assert!(decoder.error().is_none());
+    }
+
+    #[test]
+    fn container(
+        codec in prop::sample::select(Codec::ALL.to_vec()),
+        bit_width in 0..=64u8,
+        count: u32,
+        payload in prop::collection::vec(any::<u8>(), 0..300),
+    ) {
+        let block = Block { codec, bit_width, count, payload: &payload };
+        let mut bytes = Vec::new();
+        block.write(&mut bytes);
+        prop_assert_eq!(Block::read(&bytes), Ok((block, bytes.len())));
+    }
+}
@@ -0,0 +1,106 @@
+use simd_bitpacking_demo::simple8b::{self, MAX_VALUE};
+use simd_bitpacking_demo::Error;
+
+fn words(values: &[u64]) -> Vec<u64> {
+    let mut out = Vec::new();
+    simple8b::encode(values, &mut out).unwrap();
+    let mut decoded = Vec::new();
+    assert_eq!(
+        simple8b::decode(&out, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    assert_eq!(decoded, values);
+    out.chunks(8)
+        .map(|w| u64::from_le_bytes(w.try_into().unwrap()))
+        .collect()
+}
+
+fn selectors(values: &[u64]) -> Vec<u64> {
+    words(values).iter().map(|w| w >> 60).collect()
+}
+
+#[test]
+fn runs_of_ones() {
+    assert_eq!(words(&[1; 240]), [0]);
+    assert_eq!(words(&[1; 120]), [1 << 60]);
+    // 480 + 120 ones, then 60 single bits.
+    assert_eq!(selectors(&[1; 660]), [0, 0, 1, 2]);
+    // 239 ones do not make a 240-run, but 120 of them do make a shorter one.
+    assert_eq!(selectors(&[1; 239])[0], 1);
+}
+
+#[test]
+fn every_selector() {
+    // A full group of the widest value each selector holds.
+    for (selector, (n, bits)) in [
+        (60, 1),
+        (30, 2),
+        (20, 3),
+        (15, 4),
+        (12, 5),
+        (10, 6),
+        (8, 7),
+        (7, 8),
+        (6, 10),
+        (5, 12),
+        (4, 15),
+        (3, 20),
+        (2, 30),
+        (1, 60),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let values = vec![(1u64 << bits) - 1; n];
+        assert_eq!(selectors(&values), [selector as u64 + 2], "{bits} bits");
+    }
+}
+
+#[test]
+fn first_value_in_low_bits() {
+    // Eight 7-bit values.
+    let values = [1, 2, 3, 4, 5, 6, 7, 127];
+    let expected = values
+        .iter()
+        .enumerate()
+        .fold(8 << 60, |word, (i, &v)| word | v << (7 * i));
+    assert_eq!(words(&values), [expected]);
+}
+
+#[test]
+fn tail_takes_smaller_groups() {
+    // 61 zeros: 60 single bits, then one leftover value in its own word.
+    assert_eq!(selectors(&[0; 61]), [2, 15]);
+    // Five 1-bit values cannot fill a 60-value word.
+    assert_eq!(selectors(&[0, 1, 0, 1, 0]), [11]);
+    assert_eq!(selectors(&[]), []);
+}
+
+#[test]
+fn largest_value() {
+    assert_eq!(words(&[MAX_VALUE]), [15 << 60 | MAX_VALUE]);
+    let mut out = Vec::new();
+    assert_eq!(
+        simple8b::encode(&[3, MAX_VALUE + 1], &mut out),
+        Err(Error::OutOfRange("simple8b values must be below 2^60"))
+    );
+    assert!(out.is_empty());
+}
+
+#[test]
+fn malformed_input() {
+    let mut out = Vec::new();
+    simple8b::encode(&[5; 12], &mut out).unwrap();
+    assert_eq!(
+        simple8b::decode(&out, 11, &mut Vec::new()),
+        Err(Error::Corrupt("word holds more values than remain"))
+    );
+    assert_eq!(
+        simple8b::decode(&out[..7], 12, &mut Vec::new()),
+        Err(Error::Truncated)
+    );
+    assert_eq!(
+        simple8b::decode(&out, 13, &mut Vec::new()),
+        Err(Error::Truncated)
+    );
+}
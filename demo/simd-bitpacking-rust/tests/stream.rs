@@ -0,0 +1,48 @@
+use std::io::ErrorKind;
+
+use simd_bitpacking_demo::stream::{Decoder, Encoder};
+
+// Nanosecond timestamps with jitter, plus a counter reset every 1000 samples.
+fn series(len: u64) -> Vec<u64> {
+    (0..len)
+        .map(|i| 1_700_000_000_000_000_000 + i * 15_000_000_000 + (i * 7919) % 1000)
+        .chain((0..len).map(|i| i % 1000))
+        .collect()
+}
+
+#[test]
+fn round_trip_mixed_pushes() {
+    let values = series(1000);
+    let mut encoder = Encoder::new(Vec::new());
+    let (single, slices) = values.split_at(77);
+    for &v in single {
+        encoder.push(v).unwrap();
+    }
+    for chunk in slices.chunks(300) {
+        encoder.push_slice(chunk).unwrap();
+    }
+    let bytes = encoder.finish().unwrap();
+    assert!(bytes.len() < values.len() * 8 / 2);
+
+    let mut decoder = Decoder::new(bytes.as_slice());
+    assert!(decoder.by_ref().eq(values.iter().copied()));
+    assert!(decoder.error().is_none());
+}
+
+#[test]
+fn empty_stream() {
+    let bytes = Encoder::new(Vec::new()).finish().unwrap();
+    assert_eq!(bytes, [0]);
+    assert_eq!(Decoder::new(bytes.as_slice()).count(), 0);
+}
+
+#[test]
+fn missing_end_marker_is_an_error() {
+    let mut encoder = Encoder::new(Vec::new());
+    encoder.push_slice(&series(100)).unwrap();
+    let bytes = encoder.finish().unwrap();
+
+    let mut decoder = Decoder::new(&bytes[..bytes.len() - 1]);
+    assert_eq!(decoder.by_ref().count(), 200);
+    assert_eq!(decoder.error().unwrap().kind(), ErrorKind::UnexpectedEof);
+}
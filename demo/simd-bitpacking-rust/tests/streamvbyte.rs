@@ -0,0 +1,60 @@
+use simd_bitpacking_demo::streamvbyte;
+use simd_bitpacking_demo::Error;
+
+fn round_trip(values: &[u32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    streamvbyte::encode(values, &mut out);
+    let mut decoded = Vec::new();
+    assert_eq!(
+        streamvbyte::decode(&out, values.len(), &mut decoded),
+        Ok(out.len())
+    );
+    assert_eq!(decoded, values);
+    out
+}
+
+#[test]
+fn control_and_data_streams() {
+    // Codes 0, 1, 2, 3 from the low bits up, then one value in a second
+    // control byte whose unused slots stay zero.
+    let out = round_trip(&[0x01, 0x0302, 0x06_0504, 0x0a09_0807, 0x0b]);
+    assert_eq!(
+        out,
+        [0b11_10_01_00, 0b00, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0x0b]
+    );
+}
+
+#[test]
+fn every_length_and_partial_quads() {
+    let values: Vec<u32> = (0..1000u32)
+        .map(|i| i.wrapping_mul(0x9e37_79b9) >> (8 * (i % 4)))
+        .collect();
+    for len in [0, 1, 2, 3, 4, 5, 15, 16, 17, 999, 1000] {
+        round_trip(&values[..len]);
+    }
+    assert_eq!(round_trip(&[0; 8]).len(), 2 + 8);
+    assert_eq!(round_trip(&[u32::MAX; 8]).len(), 2 + 32);
+}
+
+#[test]
+fn trailing_input_is_not_consumed() {
+    let mut out = Vec::new();
+    streamvbyte::encode(&[1, 1 << 20], &mut out);
+    let len = out.len();
+    out.extend_from_slice(&[0xff; 40]);
+    let mut decoded = Vec::new();
+    assert_eq!(streamvbyte::decode(&out, 2, &mut decoded), Ok(len));
+    assert_eq!(decoded, [1, 1 << 20]);
+}
+
+#[test]
+fn truncated_streams() {
+    let out = round_trip(&[1 << 30; 5]);
+    for len in 0..out.len() {
+        assert_eq!(
+            streamvbyte::decode(&out[..len], 5, &mut Vec::new()),
+            Err(Error::Truncated),
+            "{len} bytes"
+        );
+    }
+}